@@ -0,0 +1,61 @@
+use crate::errors::ApiError;
+use domain::chessgame::ChessGame;
+use domain::clock::now_ms;
+use domain::opponent::Opponent;
+use engine::search::Engine;
+use engine::strength::Strength;
+use engine::uci::UciEngine;
+use tracing::log;
+
+// In a game against the computer, lets the bot reply when it is its turn. The search can take a
+// while at the higher levels, so it doesn't run on the async workers.
+pub async fn reply(
+    mut chess_game: ChessGame,
+    uci_engine: Option<UciEngine>,
+) -> Result<ChessGame, ApiError> {
+    let chess_game = tokio::task::spawn_blocking(move || {
+        play_bot_move(&mut chess_game, uci_engine.as_ref());
+        chess_game
+    })
+    .await
+    .map_err(anyhow::Error::new)?;
+    Ok(chess_game)
+}
+
+// The bot only knows the opponent's rule once it has been revealed, like a person would.
+fn play_bot_move(chess_game: &mut ChessGame, uci_engine: Option<&UciEngine>) {
+    let Some(color) = chess_game.opponent.bot_color() else {
+        return;
+    };
+    if chess_game.game.side_to_move() != color || chess_game.get_moves().is_empty() {
+        return;
+    }
+    let rule = chess_game.active_rule(color);
+    let uci_move = match (chess_game.opponent, uci_engine) {
+        (Opponent::Uci { .. }, Some(uci_engine)) => uci_engine
+            .best_move(&chess_game.history(), rule)
+            .map_err(|e| log::error!("UCI engine failed, the built-in bot moves instead: {}", e))
+            .ok()
+            .flatten(),
+        _ => None,
+    };
+    let chess_move = uci_move.or_else(|| {
+        let opponent_rule = if chess_game.rule_revealed(!color) {
+            chess_game.active_rule(!color)
+        } else {
+            0
+        };
+        let mut rules = [0; 2];
+        rules[color.to_index()] = rule;
+        rules[(!color).to_index()] = opponent_rule;
+        let level = match chess_game.opponent {
+            Opponent::Bot { level, .. } => level,
+            _ => Strength::Expert.level(),
+        };
+        let engine = Engine::new(Strength::from_level(level), now_ms());
+        engine.best_move(&chess_game.history(), rules)
+    });
+    if let Some(chess_move) = chess_move {
+        chess_game.make_move(chess_move.to_string(), None);
+    }
+}
@@ -1,4 +1,5 @@
 use config::{Config, ConfigError, File};
+use engine::uci::UciEngine;
 use serde::Deserialize;
 use std::env;
 
@@ -6,6 +7,34 @@ use std::env;
 pub struct ApplicationSettings {
     pub host: [u8; 4],
     pub port: u16,
+    // A UCI engine to play against, like Stockfish. Without it only the built-in bot is offered.
+    #[serde(default)]
+    pub uci_engine: Option<UciEngineSettings>,
+    // Opens the admin endpoints to requests with it as bearer token, they are closed without one.
+    #[serde(default)]
+    pub admin_token: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct UciEngineSettings {
+    pub path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_movetime_ms")]
+    pub movetime_ms: u64,
+}
+
+fn default_movetime_ms() -> u64 {
+    1000
+}
+
+impl From<UciEngineSettings> for UciEngine {
+    fn from(settings: UciEngineSettings) -> Self {
+        UciEngine {
+            args: settings.args,
+            ..UciEngine::new(settings.path, settings.movetime_ms)
+        }
+    }
 }
 
 impl ApplicationSettings {
@@ -0,0 +1,185 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use domain::analysis::AnalysisError;
+use domain::guessing::GuessError;
+use domain::pgn::PgnError;
+use serde::Serialize;
+use tracing::log;
+
+// Every error the api can answer with. HTTP handlers return it as a JSON body with a matching
+// status code, socket handlers emit the same body as the payload of an "error" event.
+#[derive(Debug, PartialEq)]
+pub enum ApiError {
+    NoGameInSession,
+    InvalidRoomCode(String),
+    GameNotFound,
+    InvalidName(String),
+    InvalidElo(i32),
+    UnknownRule(i32),
+    InvalidTimeOdds(u16, u16),
+    InvalidBotLevel(u8),
+    NoUciEngine,
+    InvalidColor,
+    InvalidMove(String),
+    NotSeated,
+    NotYourTurn,
+    NotAdmin,
+    Guess(GuessError),
+    Analysis(AnalysisError),
+    Pgn(PgnError),
+    Internal,
+}
+
+// The code is stable so clients can match on it, the message is only meant for people.
+#[derive(Serialize, Debug)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NoGameInSession => "no_game_in_session",
+            ApiError::InvalidRoomCode(_) => "invalid_room_code",
+            ApiError::GameNotFound => "game_not_found",
+            ApiError::InvalidName(_) => "invalid_name",
+            ApiError::InvalidElo(_) => "invalid_elo",
+            ApiError::UnknownRule(_) => "unknown_rule",
+            ApiError::InvalidTimeOdds(_, _) => "invalid_time_odds",
+            ApiError::InvalidBotLevel(_) => "invalid_bot_level",
+            ApiError::NoUciEngine => "no_uci_engine",
+            ApiError::InvalidColor => "invalid_color",
+            ApiError::InvalidMove(_) => "invalid_move",
+            ApiError::NotSeated => "not_seated",
+            ApiError::NotYourTurn => "not_your_turn",
+            ApiError::NotAdmin => "not_admin",
+            ApiError::Guess(GuessError::GameOver) => "game_over",
+            ApiError::Guess(GuessError::NoGuessesLeft) => "no_guesses_left",
+            ApiError::Guess(GuessError::AlreadyRevealed) => "rule_already_revealed",
+            ApiError::Analysis(AnalysisError::InvalidFen(_)) => "invalid_fen",
+            ApiError::Analysis(AnalysisError::IllegalMove(_)) => "illegal_move",
+            ApiError::Pgn(_) => "invalid_pgn",
+            ApiError::Internal => "internal",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::NoGameInSession => "There is no game for this session".to_string(),
+            ApiError::InvalidRoomCode(room) => format!("{room:?} is not a valid room code"),
+            ApiError::GameNotFound => "There is no game with this id".to_string(),
+            ApiError::InvalidName(name) => format!("{name:?} is not a valid name"),
+            ApiError::InvalidElo(elo) => format!("{elo} is not a valid elo"),
+            ApiError::UnknownRule(id) => format!("There is no Elo Stealo rule with id {id}"),
+            ApiError::InvalidTimeOdds(minutes, giver_minutes) => {
+                format!("{minutes} against {giver_minutes} minutes are not valid time odds")
+            }
+            ApiError::InvalidBotLevel(level) => format!("{level} is not a bot level"),
+            ApiError::NoUciEngine => "There is no UCI engine on this server".to_string(),
+            ApiError::InvalidColor => "Color has to be white or black".to_string(),
+            ApiError::InvalidMove(play_move) => format!("{play_move:?} is not a move"),
+            ApiError::NotSeated => "You are not seated in this room".to_string(),
+            ApiError::NotYourTurn => "It is not your turn".to_string(),
+            ApiError::NotAdmin => "This needs the admin token".to_string(),
+            ApiError::Guess(GuessError::GameOver) => "The game has already ended".to_string(),
+            ApiError::Guess(GuessError::NoGuessesLeft) => "You have no guesses left".to_string(),
+            ApiError::Guess(GuessError::AlreadyRevealed) => {
+                "The opponent's rule is already known".to_string()
+            }
+            ApiError::Analysis(AnalysisError::InvalidFen(fen)) => {
+                format!("{fen:?} is not a valid FEN")
+            }
+            ApiError::Analysis(AnalysisError::IllegalMove(chess_move)) => {
+                format!("{chess_move:?} is not a legal move in the history")
+            }
+            ApiError::Pgn(PgnError::InvalidFen(fen)) => format!("{fen:?} is not a valid FEN"),
+            ApiError::Pgn(PgnError::IllegalMove(game, chess_move)) => {
+                format!("{chess_move:?} in game {game} is not a legal move")
+            }
+            ApiError::Internal => "Something went wrong on the server".to_string(),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotSeated | ApiError::NotAdmin => StatusCode::FORBIDDEN,
+            ApiError::GameNotFound => StatusCode::NOT_FOUND,
+            ApiError::Guess(_) | ApiError::NotYourTurn => StatusCode::CONFLICT,
+            ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody {
+            code: self.code(),
+            message: self.message(),
+        }
+    }
+}
+
+impl From<GuessError> for ApiError {
+    fn from(error: GuessError) -> Self {
+        ApiError::Guess(error)
+    }
+}
+
+impl From<PgnError> for ApiError {
+    fn from(error: PgnError) -> Self {
+        ApiError::Pgn(error)
+    }
+}
+
+impl From<AnalysisError> for ApiError {
+    fn from(error: AnalysisError) -> Self {
+        ApiError::Analysis(error)
+    }
+}
+
+// Storage errors are logged here and never shown to the client, except that the game asked for
+// doesn't exist.
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        if let Some(sqlx::Error::RowNotFound) = error.downcast_ref::<sqlx::Error>() {
+            return ApiError::GameNotFound;
+        }
+        log::error!("{:?}", error);
+        ApiError::Internal
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self.body())).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body_has_stable_code() {
+        let body = serde_json::to_value(ApiError::UnknownRule(99).body()).unwrap();
+        assert_eq!(body["code"], "unknown_rule");
+        assert_eq!(body["message"], "There is no Elo Stealo rule with id 99");
+    }
+
+    #[test]
+    fn guess_errors_are_conflicts() {
+        let error = ApiError::from(GuessError::NoGuessesLeft);
+        assert_eq!(error.code(), "no_guesses_left");
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn missing_game_is_not_found() {
+        let error = ApiError::from(anyhow::Error::new(sqlx::Error::RowNotFound));
+        assert_eq!(error, ApiError::GameNotFound);
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        let error = ApiError::from(anyhow::Error::new(sqlx::Error::PoolTimedOut));
+        assert_eq!(error, ApiError::Internal);
+    }
+}
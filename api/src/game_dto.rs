@@ -1,12 +1,289 @@
-#[allow(non_snake_case)]
+use chess::Color;
+use domain::adaptive::{self, stronger_side, HandicapEvent};
+use domain::analysis::{Analysis, Outcome};
 use domain::chessgame::ChessGame;
+use domain::guessing::{GuessReward, DEFAULT_MAX_GUESSES};
+use domain::odds::{MaterialOdds, TimeOdds};
+use domain::opponent;
+use domain::restrictiveness::Restrictiveness;
+use domain::visibility::RuleVisibility;
+use persistence::game_info::{ObligationProgress, Secret};
+use persistence::stealo_rule::StealoRule;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
+// The rules are the ones everyone in the room may see, so the DTO can be broadcast as is, when
+// made by create_room_dto.
 #[derive(Deserialize, Serialize, Debug)]
 pub struct GameDTO {
     board: String,
     moves: Vec<String>,
+    // Bonus moves of the side to move, written like "+e3e5", see domain::bonus. They give away
+    // a bonus rule, so online they are left out until the rule is revealed.
+    bonus_moves: Vec<String>,
     result: String,
+    white_stealo: Secret<i32>,
+    black_stealo: Secret<i32>,
+    white_budget: Secret<Option<u16>>,
+    black_budget: Secret<Option<u16>>,
+    white_progress: Secret<Option<ObligationProgress>>,
+    black_progress: Secret<Option<ObligationProgress>>,
+    commitment: Option<String>,
+    nonce: Secret<String>,
+    handicap_events: Vec<HandicapEventDTO>,
+    // Milliseconds left on the clock right now, None for games without time odds.
+    white_time: Option<u64>,
+    black_time: Option<u64>,
+}
+
+// The adaptive handicap eased or tightened the stronger player's rule after this many moves.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct HandicapEventDTO {
+    ply: u16,
+    eased: bool,
+}
+
+impl From<HandicapEvent> for HandicapEventDTO {
+    fn from(event: HandicapEvent) -> Self {
+        Self {
+            ply: event.ply,
+            eased: event.eased,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Adaptive {
+    #[default]
+    Off,
+    Lift,
+    Milder {
+        rule_id: i32,
+    },
+}
+
+impl From<Adaptive> for adaptive::Adaptive {
+    fn from(adaptive: Adaptive) -> Self {
+        match adaptive {
+            Adaptive::Off => adaptive::Adaptive::Off,
+            Adaptive::Lift => adaptive::Adaptive::Lift,
+            Adaptive::Milder { rule_id } => adaptive::Adaptive::Milder(rule_id),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Material {
+    #[default]
+    None,
+    PawnAndMove,
+    Knight,
+    Rook,
+    Queen,
+}
+
+impl From<Material> for MaterialOdds {
+    fn from(material: Material) -> Self {
+        match material {
+            Material::None => MaterialOdds::None,
+            Material::PawnAndMove => MaterialOdds::PawnAndMove,
+            Material::Knight => MaterialOdds::Knight,
+            Material::Rook => MaterialOdds::Rook,
+            Material::Queen => MaterialOdds::Queen,
+        }
+    }
+}
+
+impl From<MaterialOdds> for Material {
+    fn from(odds: MaterialOdds) -> Self {
+        match odds {
+            MaterialOdds::None => Material::None,
+            MaterialOdds::PawnAndMove => Material::PawnAndMove,
+            MaterialOdds::Knight => Material::Knight,
+            MaterialOdds::Rook => Material::Rook,
+            MaterialOdds::Queen => Material::Queen,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub struct Time {
+    pub minutes: u16,
+    pub giver_minutes: u16,
+}
+
+impl From<Time> for TimeOdds {
+    fn from(time: Time) -> Self {
+        TimeOdds::new(time.minutes, time.giver_minutes)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    White,
+    Black,
+}
+
+impl From<Side> for Color {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::White => Color::White,
+            Side::Black => Color::Black,
+        }
+    }
+}
+
+// Who plays the other side of a local game, the bot plays the given side.
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Opponent {
+    #[default]
+    Hotseat,
+    Bot {
+        side: Side,
+        level: u8,
+    },
+    // The UCI engine configured on the server.
+    Uci {
+        side: Side,
+    },
+}
+
+impl From<Opponent> for opponent::Opponent {
+    fn from(opponent: Opponent) -> Self {
+        match opponent {
+            Opponent::Hotseat => opponent::Opponent::Hotseat,
+            Opponent::Bot { side, level } => opponent::Opponent::Bot {
+                color: side.into(),
+                level,
+            },
+            Opponent::Uci { side } => opponent::Opponent::Uci { color: side.into() },
+        }
+    }
+}
+
+// Material and time odds, given by the stronger player unless the giver is set.
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(default)]
+pub struct Odds {
+    pub material: Material,
+    pub time: Option<Time>,
+    pub giver: Option<Side>,
+}
+
+impl Odds {
+    pub fn giver(&self, elo_white: i32, elo_black: i32) -> Color {
+        match self.giver {
+            Some(side) => side.into(),
+            None => stronger_side(elo_white, elo_black).unwrap_or(Color::White),
+        }
+    }
+}
+
+// The handicaps besides Elo Stealo rules, with their elo for the balancing in the client.
+#[derive(Serialize)]
+pub struct Handicaps {
+    material: Vec<MaterialHandicap>,
+    time: Vec<TimeHandicap>,
+}
+
+#[derive(Serialize)]
+pub struct MaterialHandicap {
+    odds: Material,
+    name: &'static str,
+    elo: i32,
+}
+
+#[derive(Serialize)]
+pub struct TimeHandicap {
+    odds: Time,
+    name: String,
+    elo: i32,
+}
+
+impl Handicaps {
+    pub fn catalogue() -> Self {
+        Self {
+            material: MaterialOdds::ALL
+                .into_iter()
+                .map(|odds| MaterialHandicap {
+                    odds: odds.into(),
+                    name: odds.name(),
+                    elo: odds.elo(),
+                })
+                .collect(),
+            time: TimeOdds::PRESETS
+                .into_iter()
+                .map(|odds| TimeHandicap {
+                    odds: Time {
+                        minutes: odds.minutes,
+                        giver_minutes: odds.giver_minutes,
+                    },
+                    name: odds.name(),
+                    elo: odds.elo(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum Visibility {
+    Open,
+    #[default]
+    Hidden,
+    RevealAfter {
+        moves: u16,
+    },
+    RevealOnForbiddenAttempt,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct GuessSettings {
+    pub max_guesses: u8,
+    pub reward: Reward,
+}
+
+impl Default for GuessSettings {
+    fn default() -> Self {
+        Self {
+            max_guesses: DEFAULT_MAX_GUESSES,
+            reward: Reward::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Reward {
+    #[default]
+    Points,
+    RevealAndLift,
+}
+
+impl From<Reward> for GuessReward {
+    fn from(reward: Reward) -> Self {
+        match reward {
+            Reward::Points => GuessReward::Points,
+            Reward::RevealAndLift => GuessReward::RevealAndLift,
+        }
+    }
+}
+
+impl From<Visibility> for RuleVisibility {
+    fn from(visibility: Visibility) -> Self {
+        match visibility {
+            Visibility::Open => RuleVisibility::Open,
+            Visibility::Hidden => RuleVisibility::Hidden,
+            Visibility::RevealAfter { moves } => RuleVisibility::RevealAfter(moves),
+            Visibility::RevealOnForbiddenAttempt => RuleVisibility::RevealOnForbiddenAttempt,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -17,6 +294,16 @@ pub struct NewLocalGame {
     pub elo2: i32,
     pub stealo1: i32,
     pub stealo2: i32,
+    #[serde(default)]
+    pub visibility: Visibility,
+    #[serde(default)]
+    pub guessing: GuessSettings,
+    #[serde(default)]
+    pub adaptive: Adaptive,
+    #[serde(default)]
+    pub odds: Odds,
+    #[serde(default)]
+    pub opponent: Opponent,
 }
 
 #[derive(Deserialize)]
@@ -34,11 +321,40 @@ pub struct NewOnlineGame {
     pub elo2: i32,
     pub stealo1: i32,
     pub stealo2: i32,
+    #[serde(default)]
+    pub visibility: Visibility,
+    #[serde(default)]
+    pub guessing: GuessSettings,
+    #[serde(default)]
+    pub adaptive: Adaptive,
+    #[serde(default)]
+    pub odds: Odds,
+}
+
+#[derive(Deserialize)]
+pub struct Guess {
+    pub rule_id: i32,
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OnlineGuess {
+    pub roomcode: String,
+    pub seat: String,
+    pub rule_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct GuessResult {
+    pub correct: bool,
+    pub guesses_left: u8,
+    pub game: GameDTO,
 }
 
 #[derive(Deserialize)]
 pub struct PlayOnlineMove {
     pub roomcode: String,
+    pub seat: String,
     pub play_move: String,
 }
 
@@ -48,12 +364,41 @@ pub struct WaitingPlayer {
     pub name: String,
     pub elo: String,
     pub stealo: i32,
+    // The token from an earlier visit to the room, never passed on to the other player.
+    #[serde(default, skip_serializing)]
+    pub seat: Option<String>,
+}
+
+// Sent to the socket that got a seat, again after every reconnect.
+#[derive(Serialize)]
+pub struct Seat {
+    pub room: String,
+    pub token: String,
+    pub color: String,
+}
+
+impl Seat {
+    pub fn new(room: &str, token: Uuid, color: Color) -> Self {
+        Self {
+            room: room.to_string(),
+            token: token.to_string(),
+            color: color_name(color).to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Reconnect {
+    pub room: String,
+    #[serde(default)]
+    pub seat: String,
 }
 
+// The seat is the token handed out by the socket when the player created or joined the room.
 #[derive(Deserialize)]
 pub struct GetInfo {
     pub roomcode: String,
-    pub color: String,
+    pub seat: String,
 }
 
 #[derive(Serialize)]
@@ -64,11 +409,139 @@ pub struct GameInfoLocal {
     pub black_elo: i32,
     pub white_stealo: i32,
     pub black_stealo: i32,
+    pub white_stealo_revealed: bool,
+    pub black_stealo_revealed: bool,
+    pub white_guess_points: u32,
+    pub black_guess_points: u32,
+    pub white_budget: Option<u16>,
+    pub black_budget: Option<u16>,
+    pub white_progress: Option<ObligationProgress>,
+    pub black_progress: Option<ObligationProgress>,
+}
+
+// The history is played from the FEN, the analysis is for the side to move after it.
+#[derive(Deserialize)]
+pub struct Analyze {
+    pub fen: String,
+    #[serde(default)]
+    pub history: Vec<String>,
+    pub rule_ids: Vec<i32>,
+}
+
+#[derive(Serialize)]
+pub struct AnalysisDTO {
+    side_to_move: String,
+    allowed: Vec<String>,
+    forbidden: Vec<ForbiddenMove>,
+    // Only when no moves are allowed, with the result like in a game.
+    outcome: Option<OutcomeDTO>,
+}
+
+#[derive(Serialize)]
+pub struct ForbiddenMove {
+    play_move: String,
+    reasons: Vec<ForbiddenBy>,
+}
+
+#[derive(Serialize)]
+pub struct ForbiddenBy {
+    rule_id: i32,
+    name: String,
+    description: String,
+}
+
+#[derive(Serialize)]
+pub struct OutcomeDTO {
+    reason: &'static str,
+    result: String,
+}
+
+impl AnalysisDTO {
+    // Rules missing from the list are still named by their id.
+    pub fn new(analysis: Analysis, rules: &[StealoRule]) -> Self {
+        let forbidden_by = |rule_id: i32| match rules.iter().find(|rule| rule.id == rule_id) {
+            Some(rule) => ForbiddenBy {
+                rule_id,
+                name: rule.name.clone(),
+                description: rule.description.clone(),
+            },
+            None => ForbiddenBy {
+                rule_id,
+                name: format!("Rule {rule_id}"),
+                description: String::new(),
+            },
+        };
+        let side = analysis.side_to_move;
+        Self {
+            side_to_move: color_name(side).to_string(),
+            allowed: analysis.allowed.iter().map(|m| m.to_string()).collect(),
+            forbidden: analysis
+                .forbidden
+                .into_iter()
+                .map(|(chess_move, rule_ids)| ForbiddenMove {
+                    play_move: chess_move.to_string(),
+                    reasons: rule_ids.into_iter().map(forbidden_by).collect(),
+                })
+                .collect(),
+            outcome: analysis.outcome.map(|outcome| OutcomeDTO {
+                reason: match outcome {
+                    Outcome::Checkmate => "checkmate",
+                    Outcome::Stalemate => "stalemate",
+                    Outcome::NoAllowedMoves => "no_allowed_moves",
+                },
+                result: outcome.winner(side).map_or("draw", color_name).to_string(),
+            }),
+        }
+    }
+}
+
+// Measures rules on the positions of the games in the PGN, or of the most recent stored games.
+// With a bot level the bot finds the best move of every position, which takes a while.
+#[derive(Deserialize)]
+pub struct MeasureRestrictiveness {
+    // All rules when left out.
+    pub rule_ids: Option<Vec<i32>>,
+    pub pgn: Option<String>,
+    #[serde(default = "default_corpus_games")]
+    pub games: i64,
+    pub level: Option<u8>,
+}
+
+fn default_corpus_games() -> i64 {
+    100
+}
+
+#[derive(Serialize)]
+pub struct RestrictivenessDTO {
+    rule_id: i32,
+    positions: usize,
+    removed: f64,
+    best_move_forbidden: Option<f64>,
+    no_moves: f64,
+}
+
+impl From<&Restrictiveness> for RestrictivenessDTO {
+    fn from(result: &Restrictiveness) -> Self {
+        Self {
+            rule_id: result.rule_id,
+            positions: result.positions,
+            removed: result.removed(),
+            best_move_forbidden: result.best_move_forbidden(),
+            no_moves: result.no_moves(),
+        }
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
 }
 
 pub fn create_game_dto(chess_game: &ChessGame) -> GameDTO {
     let available_moves = chess_game.get_moves_string();
-    let game_result = if available_moves.len() != 0 {
+    let game_result = if !available_moves.is_empty() {
         "none".to_string()
     } else {
         chess_game.winner_when_no_moves()
@@ -77,7 +550,51 @@ pub fn create_game_dto(chess_game: &ChessGame) -> GameDTO {
     let game_dto = GameDTO {
         board: format!("{}", chess_game.game.current_position()),
         moves: available_moves,
+        bonus_moves: chess_game.get_bonus_moves_string(),
         result: game_result,
+        white_stealo: Secret::reveal_if(
+            chess_game.rule_revealed(Color::White),
+            chess_game.rule_id_white,
+        ),
+        black_stealo: Secret::reveal_if(
+            chess_game.rule_revealed(Color::Black),
+            chess_game.rule_id_black,
+        ),
+        white_budget: Secret::reveal_if(
+            chess_game.rule_revealed(Color::White),
+            chess_game.remaining_budget(Color::White),
+        ),
+        black_budget: Secret::reveal_if(
+            chess_game.rule_revealed(Color::Black),
+            chess_game.remaining_budget(Color::Black),
+        ),
+        white_progress: Secret::reveal_if(
+            chess_game.rule_revealed(Color::White),
+            chess_game.obligation_progress(Color::White).map(Into::into),
+        ),
+        black_progress: Secret::reveal_if(
+            chess_game.rule_revealed(Color::Black),
+            chess_game.obligation_progress(Color::Black).map(Into::into),
+        ),
+        commitment: chess_game.commitment(),
+        nonce: chess_game.revealed_nonce().map(str::to_string).into(),
+        handicap_events: chess_game
+            .handicap_events()
+            .into_iter()
+            .map(HandicapEventDTO::from)
+            .collect(),
+        white_time: chess_game.time_left(Color::White),
+        black_time: chess_game.time_left(Color::Black),
     };
     game_dto
 }
+
+// For online games, the bonus moves are only shown to the room once the rule is revealed. Until
+// then their owner gets them from the game info, like the rule itself.
+pub fn create_room_dto(chess_game: &ChessGame) -> GameDTO {
+    let mut game_dto = create_game_dto(chess_game);
+    if !chess_game.rule_revealed(chess_game.game.side_to_move()) {
+        game_dto.bonus_moves.clear();
+    }
+    game_dto
+}
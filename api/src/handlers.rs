@@ -1,12 +1,27 @@
-use std::str::FromStr;
+use crate::bot;
+use crate::errors::ApiError;
 use crate::game_dto::{
-    create_game_dto, GameDTO, GameInfoLocal, GetInfo, NewLocalGame, NewOnlineGame, PlayMove,
+    create_game_dto, create_room_dto, Adaptive, AnalysisDTO, Analyze, GameDTO, GameInfoLocal,
+    GetInfo, Guess, GuessResult, Handicaps, MeasureRestrictiveness, NewLocalGame, NewOnlineGame,
+    PlayMove, RestrictivenessDTO,
+};
+use crate::validation::{
+    authorize_admin, parse_room_code, validate_bot_level, validate_move, validate_opponent,
+    validate_player, validate_rule, validate_time_odds,
 };
 use crate::AppState;
 use axum::extract::State;
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap};
 use axum::Json;
+use chess::Color;
+use domain::analysis::analyze;
 use domain::chessgame::ChessGame;
+use domain::filters::history::History;
+use domain::move_generator::HIGHEST_FILTER_ID;
+use domain::pgn::parse_pgn;
+use engine::simulation::{measure_restrictiveness, Player};
+use engine::strength::Strength;
+use domain::odds::TimeOdds;
 use tower_sessions::Session;
 use tracing::log;
 use uuid::Uuid;
@@ -18,64 +33,79 @@ pub async fn start_game(
     State(state): State<AppState>,
     session: Session,
     Json(new_game): Json<NewLocalGame>,
-) -> Result<Json<GameDTO>, StatusCode> {
-    println!("{:?}", session.id());
+) -> Result<Json<GameDTO>, ApiError> {
+    validate_player(&new_game.player1, new_game.elo1, new_game.stealo1)?;
+    validate_player(&new_game.player2, new_game.elo2, new_game.stealo2)?;
+    if let Adaptive::Milder { rule_id } = new_game.adaptive {
+        validate_rule(rule_id)?;
+    }
+    validate_time_odds(new_game.odds.time.map(TimeOdds::from))?;
+    validate_opponent(new_game.opponent.into(), state.uci_engine.is_some())?;
     let p1 = new_game.player1;
     let p2 = new_game.player2;
     let elo1 = new_game.elo1;
     let elo2 = new_game.elo2;
     let stealo1 = new_game.stealo1;
     let stealo2 = new_game.stealo2;
+    let visibility = new_game.visibility;
+    let guessing = new_game.guessing;
+    let adaptive = new_game.adaptive;
+    let odds = new_game.odds;
+    let opponent = new_game.opponent;
     let id = Uuid::now_v7();
-    session.insert("gameId", id.to_string()).await.unwrap();
-    let new_game = domain::chessgame::new_game(p1, p2, elo1, elo2, stealo1, stealo2);
+    session
+        .insert("gameId", id.to_string())
+        .await
+        .map_err(|e| ApiError::from(anyhow::Error::new(e)))?;
+    let mut new_game = domain::chessgame::new_game(p1, p2, elo1, elo2, stealo1, stealo2);
+    new_game.visibility = visibility.into();
+    new_game.max_guesses = guessing.max_guesses;
+    new_game.guess_reward = guessing.reward.into();
+    new_game.commit_rules(Uuid::new_v4().simple().to_string());
+    new_game.adaptive = adaptive.into();
+    new_game.give_odds(
+        odds.material.into(),
+        odds.time.map(TimeOdds::from),
+        odds.giver(elo1, elo2),
+    );
+    new_game.opponent = opponent.into();
+    // A bot playing white opens the game.
+    let new_game = bot::reply(new_game, state.uci_engine.clone()).await?;
     let game_dto = create_game_dto(&new_game);
-    match state.repository.save_game(id, new_game).await {
-        Ok(()) => Ok(Json(game_dto)),
-        Err(_e) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    state.repository.save_game(id, new_game).await?;
+    Ok(Json(game_dto))
 }
 
 pub async fn play(
     State(state): State<AppState>,
     session: Session,
     Json(play_move): Json<PlayMove>,
-) -> Result<Json<GameDTO>, StatusCode> {
-    let id = match session.get("gameId").await.unwrap() {
-        Some(id) => id,
-        None => {
-            log::error!("No gameId set for session");
-            return Err(StatusCode::BAD_REQUEST);
-        }
-    };
-    let mut chess_game: ChessGame = state.repository.get_game(id)
-        .await
-        .map_err(|_e| return StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Json<GameDTO>, ApiError> {
+    validate_move(&play_move.play_move, play_move.color.as_deref())?;
+    let id = session_game_id(&session).await?;
+    let mut chess_game: ChessGame = state.repository.get_game(id).await?;
     chess_game.make_move(play_move.play_move, play_move.color);
-    match state.repository.update_game(id, &chess_game).await {
-        Ok(()) => {
-            let game_dto = create_game_dto(&chess_game);
-            Ok(Json(game_dto))
-        }
-        Err(_e) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let chess_game = bot::reply(chess_game, state.uci_engine.clone()).await?;
+    state.repository.update_game(id, &chess_game).await?;
+    let game_dto = create_game_dto(&chess_game);
+    Ok(Json(game_dto))
 }
 
 pub async fn get_local_info(
     State(state): State<AppState>,
     session: Session,
-) -> Result<Json<GameInfoLocal>, StatusCode> {
-    let id: Uuid = match session.get("gameId").await.unwrap() {
-        Some(id) => id,
-        None => {
-            log::error!("No gameId set for session");
-            return Err(StatusCode::BAD_REQUEST);
-        }
-    };
-    let chess_game: ChessGame = state.repository.get_game(id)
-        .await
-        .map_err(|_e| return StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Json<GameInfoLocal>, ApiError> {
+    let id = session_game_id(&session).await?;
+    let chess_game: ChessGame = state.repository.get_game(id).await?;
     let local_game_info = GameInfoLocal {
+        white_stealo_revealed: chess_game.rule_revealed(Color::White),
+        black_stealo_revealed: chess_game.rule_revealed(Color::Black),
+        white_guess_points: chess_game.guess_points(Color::White),
+        black_guess_points: chess_game.guess_points(Color::Black),
+        white_budget: chess_game.remaining_budget(Color::White),
+        black_budget: chess_game.remaining_budget(Color::Black),
+        white_progress: chess_game.obligation_progress(Color::White).map(Into::into),
+        black_progress: chess_game.obligation_progress(Color::Black).map(Into::into),
         white: chess_game.white,
         black: chess_game.black,
         white_elo: chess_game.elo_white,
@@ -86,42 +116,153 @@ pub async fn get_local_info(
     Ok(Json(local_game_info))
 }
 
+// Hotseat games pass the guessing color like they do for resigning.
+pub async fn guess_rule(
+    State(state): State<AppState>,
+    session: Session,
+    Json(guess): Json<Guess>,
+) -> Result<Json<GuessResult>, ApiError> {
+    let id = session_game_id(&session).await?;
+    let guesser = match guess.color.as_deref() {
+        Some("white") => Color::White,
+        Some("black") => Color::Black,
+        _ => return Err(ApiError::InvalidColor),
+    };
+    validate_rule(guess.rule_id)?;
+    let mut chess_game: ChessGame = state.repository.get_game(id).await?;
+    let correct = chess_game.guess_rule(guesser, guess.rule_id)?;
+    state.repository.update_game(id, &chess_game).await?;
+    Ok(Json(GuessResult {
+        correct,
+        guesses_left: chess_game.guesses_left(guesser),
+        game: create_game_dto(&chess_game),
+    }))
+}
+
 // Sends an empty vector if it fails
 pub async fn stealo_rules(State(state): State<AppState>) -> Json<Vec<StealoRule>> {
     let rules = state.repository.get_stealo_rules().await.unwrap_or(Vec::new());
     Json(rules)
 }
 
+pub async fn handicaps() -> Json<Handicaps> {
+    Json(Handicaps::catalogue())
+}
+
+// What a player under these rules may do in any position, no game is needed.
+pub async fn analyze_position(
+    State(state): State<AppState>,
+    Json(request): Json<Analyze>,
+) -> Result<Json<AnalysisDTO>, ApiError> {
+    for rule_id in &request.rule_ids {
+        validate_rule(*rule_id)?;
+    }
+    let analysis = analyze(&request.fen, &request.history, &request.rule_ids)?;
+    let rules = state.repository.get_stealo_rules().await.unwrap_or(Vec::new());
+    Ok(Json(AnalysisDTO::new(analysis, &rules)))
+}
+
+// Admin
+// At most this many stored games are measured in one request.
+const MAX_CORPUS_GAMES: i64 = 1000;
+
+pub async fn rule_restrictiveness(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<MeasureRestrictiveness>,
+) -> Result<Json<Vec<RestrictivenessDTO>>, ApiError> {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    authorize_admin(authorization, state.admin_token.as_deref())?;
+    let rule_ids = request
+        .rule_ids
+        .unwrap_or_else(|| (1..=HIGHEST_FILTER_ID).collect());
+    for rule_id in &rule_ids {
+        validate_rule(*rule_id)?;
+    }
+    let engine = match request.level {
+        Some(level) => {
+            validate_bot_level(level)?;
+            Some(Player::Bot(Strength::from_level(level)))
+        }
+        None => None,
+    };
+    let games: Vec<History> = match request.pgn {
+        Some(pgn) => parse_pgn(&pgn)?,
+        None => state
+            .repository
+            .get_recent_games(request.games.clamp(1, MAX_CORPUS_GAMES))
+            .await?
+            .iter()
+            .map(ChessGame::history)
+            .collect(),
+    };
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let results = tokio::task::spawn_blocking(move || {
+        measure_restrictiveness(&games, &rule_ids, engine.as_ref(), threads)
+    })
+    .await
+    .map_err(anyhow::Error::new)?
+    .map_err(anyhow::Error::new)?;
+    Ok(Json(results.iter().map(RestrictivenessDTO::from).collect()))
+}
+
 // Online play
 pub async fn start_online(
     State(state): State<AppState>,
     Json(new_game): Json<NewOnlineGame>,
-) -> Result<Json<GameDTO>, StatusCode> {
-    let id = new_game.roomcode;
+) -> Result<Json<GameDTO>, ApiError> {
+    let id = parse_room_code(&new_game.roomcode)?;
+    validate_player(&new_game.player1, new_game.elo1, new_game.stealo1)?;
+    validate_player(&new_game.player2, new_game.elo2, new_game.stealo2)?;
+    if let Adaptive::Milder { rule_id } = new_game.adaptive {
+        validate_rule(rule_id)?;
+    }
+    validate_time_odds(new_game.odds.time.map(TimeOdds::from))?;
     let p1 = new_game.player1;
     let p2 = new_game.player2;
     let elo1 = new_game.elo1;
     let elo2 = new_game.elo2;
     let stealo1 = new_game.stealo1;
     let stealo2 = new_game.stealo2;
-    let new_game = domain::chessgame::new_game(p1, p2, elo1, elo2, stealo1, stealo2);
-    let game_dto = create_game_dto(&new_game);
-    match state.repository.save_game(Uuid::from_str(&id).unwrap(), new_game).await {
-        Ok(()) => Ok(Json(game_dto)),
-        Err(_e) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let visibility = new_game.visibility;
+    let guessing = new_game.guessing;
+    let adaptive = new_game.adaptive;
+    let odds = new_game.odds;
+    let mut new_game = domain::chessgame::new_game(p1, p2, elo1, elo2, stealo1, stealo2);
+    new_game.visibility = visibility.into();
+    new_game.max_guesses = guessing.max_guesses;
+    new_game.guess_reward = guessing.reward.into();
+    new_game.commit_rules(Uuid::new_v4().simple().to_string());
+    new_game.adaptive = adaptive.into();
+    new_game.give_odds(
+        odds.material.into(),
+        odds.time.map(TimeOdds::from),
+        odds.giver(elo1, elo2),
+    );
+    let game_dto = create_room_dto(&new_game);
+    state.repository.save_game(id, new_game).await?;
+    Ok(Json(game_dto))
 }
 
 pub async fn get_game_info(
     State(state): State<AppState>,
     Json(get_rule): Json<GetInfo>,
-) -> Result<Json<GameInfo>, StatusCode> {
-    let game_info = state.repository.load_game_info(Uuid::from_str(&get_rule.roomcode).unwrap(), get_rule.color).await;
-    match game_info {
-        Ok(info) => Ok(Json(info)),
-        Err(e) => {
-            log::error!("Failed to fetch game info: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+) -> Result<Json<GameInfo>, ApiError> {
+    let id = parse_room_code(&get_rule.roomcode)?;
+    let viewer = state.seats.color_of(&get_rule.roomcode, &get_rule.seat);
+    let game_info = state.repository.load_game_info(id, viewer).await?;
+    Ok(Json(game_info))
+}
+
+async fn session_game_id(session: &Session) -> Result<Uuid, ApiError> {
+    let id: Option<Uuid> = session
+        .get("gameId")
+        .await
+        .map_err(|e| ApiError::from(anyhow::Error::new(e)))?;
+    id.ok_or_else(|| {
+        log::error!("No gameId set for session");
+        ApiError::NoGameInSession
+    })
 }
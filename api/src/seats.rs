@@ -0,0 +1,106 @@
+use chess::Color;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// Online players are bound to a seat (room + color) when their socket creates or joins a room.
+// The server hands out a random token for that seat, and every request for hidden information
+// has to present it, so a client can no longer pick the color it wants to see the info of.
+#[derive(Clone, Default)]
+pub struct SeatRegistry {
+    seats: Arc<DashMap<(String, Color), Uuid>>,
+}
+
+impl SeatRegistry {
+    // Returns None if the seat has already been claimed by another socket. A player coming back
+    // after a reload presents the token it had, and keeps the seat.
+    pub fn claim(&self, room: &str, color: Color, previous: Option<&str>) -> Option<Uuid> {
+        match self.seats.entry((room.to_string(), color)) {
+            Entry::Vacant(seat) => Some(*seat.insert(Uuid::new_v4())),
+            Entry::Occupied(seat) => {
+                let previous = Uuid::parse_str(previous?).ok()?;
+                (*seat.get() == previous).then_some(previous)
+            }
+        }
+    }
+
+    // Frees both seats once the game has ended or was abandoned, after that the room's game info
+    // is public anyway.
+    pub fn release(&self, room: &str) {
+        for color in [Color::White, Color::Black] {
+            self.seats.remove(&(room.to_string(), color));
+        }
+    }
+
+    // The color the token was issued for in this room, or None for spectators and bad tokens.
+    pub fn color_of(&self, room: &str, token: &str) -> Option<Color> {
+        let token = Uuid::parse_str(token).ok()?;
+        [Color::White, Color::Black].into_iter().find(|&color| {
+            self.seats
+                .get(&(room.to_string(), color))
+                .is_some_and(|seat| *seat == token)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claimed_seat_resolves_to_color() {
+        let seats = SeatRegistry::default();
+        let white = seats.claim("room", Color::White, None).unwrap();
+        let black = seats.claim("room", Color::Black, None).unwrap();
+        assert_eq!(
+            seats.color_of("room", &white.to_string()),
+            Some(Color::White)
+        );
+        assert_eq!(
+            seats.color_of("room", &black.to_string()),
+            Some(Color::Black)
+        );
+    }
+
+    #[test]
+    fn seat_cant_be_claimed_twice() {
+        let seats = SeatRegistry::default();
+        seats.claim("room", Color::White, None).unwrap();
+        assert!(seats.claim("room", Color::White, None).is_none());
+    }
+
+    #[test]
+    fn unknown_token_is_a_spectator() {
+        let seats = SeatRegistry::default();
+        let white = seats.claim("room", Color::White, None).unwrap();
+        assert_eq!(seats.color_of("other room", &white.to_string()), None);
+        assert_eq!(seats.color_of("room", "white"), None);
+    }
+
+    #[test]
+    fn previous_token_reclaims_the_seat() {
+        let seats = SeatRegistry::default();
+        let white = seats.claim("room", Color::White, None).unwrap();
+        let black = seats.claim("room", Color::Black, None).unwrap();
+        assert_eq!(
+            seats.claim("room", Color::White, Some(&white.to_string())),
+            Some(white)
+        );
+        assert!(seats
+            .claim("room", Color::White, Some(&black.to_string()))
+            .is_none());
+        assert!(seats.claim("room", Color::White, Some("white")).is_none());
+    }
+
+    #[test]
+    fn released_seats_are_free_again() {
+        let seats = SeatRegistry::default();
+        let white = seats.claim("room", Color::White, None).unwrap();
+        seats.claim("other room", Color::White, None).unwrap();
+        seats.release("room");
+        assert_eq!(seats.color_of("room", &white.to_string()), None);
+        assert!(seats.claim("room", Color::White, None).is_some());
+        assert!(seats.claim("other room", Color::White, None).is_none());
+    }
+}
@@ -1,7 +1,12 @@
-use std::str::FromStr;
-use crate::game_dto::{create_game_dto, GameDTO, PlayOnlineMove, WaitingPlayer};
+use crate::errors::ApiError;
+use crate::game_dto::{
+    create_room_dto, GameDTO, GuessResult, OnlineGuess, PlayOnlineMove, Reconnect, Seat,
+    WaitingPlayer,
+};
+use crate::validation::{parse_room_code, validate_move, validate_rule};
 use crate::AppState;
 use async_timers::PeriodicTimer;
+use chess::Color;
 use socketioxide::extract::{Data, SocketRef, State};
 use std::time::Duration;
 use tracing::log;
@@ -14,20 +19,28 @@ pub async fn on_connect(socket: SocketRef) {
         let _ = socket.emit("connected", ());
     };
 
+    // A player presenting its seat token gets it again, the new socket is bound to the same seat.
     socket.on(
         "reconnected",
-        |socket: SocketRef, Data::<String>(room), state: State<AppState>| async move {
+        |socket: SocketRef, Data::<Reconnect>(reconnect), state: State<AppState>| async move {
+            let room = reconnect.room;
             log::info!("Socket {:?} reconnected to room {:?}", socket.id, &room);
+            let id = match parse_room_code(&room) {
+                Ok(id) => id,
+                Err(e) => return emit_error(&socket, e),
+            };
             let _ = socket.join(room.clone());
-            let load_chessgame = state.repository.get_game(Uuid::from_str(&room).unwrap()).await;
-            match load_chessgame {
+            if let Some(color) = state.seats.color_of(&room, &reconnect.seat) {
+                if let Some(token) = state.seats.claim(&room, color, Some(&reconnect.seat)) {
+                    socket.emit("seat", Seat::new(&room, token, color)).ok();
+                }
+            }
+            match state.repository.get_game(id).await {
                 Ok(chessgame) => {
-                    let game_dto = create_game_dto(&chessgame);
+                    let game_dto = create_room_dto(&chessgame);
                     let _ = socket.within(room).emit("sync", game_dto);
                 }
-                Err(_e) => {
-                    let _ = socket.emit("error", ());
-                }
+                Err(e) => emit_error(&socket, e.into()),
             }
         },
     );
@@ -35,8 +48,15 @@ pub async fn on_connect(socket: SocketRef) {
     // Split up create and join room
     socket.on(
         "create_room",
-        |socket: SocketRef, Data::<WaitingPlayer>(player)| async move {
+        |socket: SocketRef, Data::<WaitingPlayer>(player), state: State<AppState>| async move {
             let _ = socket.leave_all();
+            let Some(seat) = state.seats.claim(&player.room, Color::White, None) else {
+                socket.emit("full", ()).ok();
+                return;
+            };
+            socket
+                .emit("seat", Seat::new(&player.room, seat, Color::White))
+                .ok();
             let _ = socket.join(player.room);
             log::info!(
                 "{:?} created room {:?}",
@@ -49,14 +69,32 @@ pub async fn on_connect(socket: SocketRef) {
 
     socket.on(
         "join",
-        |socket: SocketRef, Data::<WaitingPlayer>(player)| async move {
+        |socket: SocketRef, Data::<WaitingPlayer>(player), state: State<AppState>| async move {
             let _ = socket.leave_all();
-            let length = socket.within(player.room.clone()).sockets().unwrap().len();
+            // After a reload the player can come back to the seat it had with its old token.
+            let rejoined = player.seat.as_deref().and_then(|seat| {
+                let color = state.seats.color_of(&player.room, seat)?;
+                Some((color, state.seats.claim(&player.room, color, Some(seat))?))
+            });
+            if let Some((color, seat)) = rejoined {
+                return rejoin(&socket, &state, &player.room, color, seat).await;
+            }
+            let length = match socket.within(player.room.clone()).sockets() {
+                Ok(sockets) => sockets.len(),
+                Err(e) => return emit_error(&socket, anyhow::Error::new(e).into()),
+            };
             if length >= 2 {
                 let _ = socket.emit("full", ()).ok();
             } else if length == 0 {
                 socket.emit("room_not_found", ()).ok();
             } else {
+                let Some(seat) = state.seats.claim(&player.room, Color::Black, None) else {
+                    socket.emit("full", ()).ok();
+                    return;
+                };
+                socket
+                    .emit("seat", Seat::new(&player.room, seat, Color::Black))
+                    .ok();
                 let _ = socket.join(player.room.clone());
                 log::info!(
                     "{:?} joined room {:?}",
@@ -89,27 +127,76 @@ pub async fn on_connect(socket: SocketRef) {
         },
     );
 
+    // Only the player seated on the side to move may move.
     socket.on(
         "move",
         |socket: SocketRef, Data::<PlayOnlineMove>(play_move), state: State<AppState>| async move {
             let room = play_move.roomcode;
-            let load_chessgame = state.repository.get_game(Uuid::from_str(&room).unwrap()).await;
-            match load_chessgame {
-                Ok(mut chessgame) => {
-                    chessgame.make_move(play_move.play_move, None);
-                    match state.repository.update_game(Uuid::from_str(&room).unwrap(), &chessgame).await {
-                        Ok(()) => {
-                            let game_dto = create_game_dto(&chessgame);
-                            let _ = socket.within(room).emit("sync", game_dto);
-                        }
-                        Err(_e) => {
-                            socket.emit("error", ()).ok();
-                        }
+            let id = match parse_room_code(&room) {
+                Ok(id) => id,
+                Err(e) => return emit_error(&socket, e),
+            };
+            let Some(mover) = state.seats.color_of(&room, &play_move.seat) else {
+                return emit_error(&socket, ApiError::NotSeated);
+            };
+            if let Err(e) = validate_move(&play_move.play_move, None) {
+                return emit_error(&socket, e);
+            }
+            let mut chessgame = match state.repository.get_game(id).await {
+                Ok(chessgame) => chessgame,
+                Err(e) => return emit_error(&socket, e.into()),
+            };
+            if chessgame.game.side_to_move() != mover {
+                return emit_error(&socket, ApiError::NotYourTurn);
+            }
+            chessgame.make_move(play_move.play_move, None);
+            match state.repository.update_game(id, &chessgame).await {
+                Ok(()) => {
+                    if chessgame.get_moves().is_empty() {
+                        state.seats.release(&room);
                     }
+                    let game_dto = create_room_dto(&chessgame);
+                    let _ = socket.within(room).emit("sync", game_dto);
                 }
-                Err(_e) => {
-                    socket.emit("error", ()).ok();
+                Err(e) => emit_error(&socket, e.into()),
+            }
+        },
+    );
+
+    // The guesser is taken from the seat, so a player can only spend their own guesses.
+    socket.on(
+        "guess",
+        |socket: SocketRef, Data::<OnlineGuess>(guess), state: State<AppState>| async move {
+            let room = guess.roomcode;
+            let id = match parse_room_code(&room) {
+                Ok(id) => id,
+                Err(e) => return emit_error(&socket, e),
+            };
+            let Some(guesser) = state.seats.color_of(&room, &guess.seat) else {
+                return emit_error(&socket, ApiError::NotSeated);
+            };
+            if let Err(e) = validate_rule(guess.rule_id) {
+                return emit_error(&socket, e);
+            }
+            let mut chessgame = match state.repository.get_game(id).await {
+                Ok(chessgame) => chessgame,
+                Err(e) => return emit_error(&socket, e.into()),
+            };
+            let correct = match chessgame.guess_rule(guesser, guess.rule_id) {
+                Ok(correct) => correct,
+                Err(e) => return emit_error(&socket, e.into()),
+            };
+            match state.repository.update_game(id, &chessgame).await {
+                Ok(()) => {
+                    let result = GuessResult {
+                        correct,
+                        guesses_left: chessgame.guesses_left(guesser),
+                        game: create_room_dto(&chessgame),
+                    };
+                    socket.emit("guess_result", result).ok();
+                    let _ = socket.within(room).emit("sync", create_room_dto(&chessgame));
                 }
+                Err(e) => emit_error(&socket, e.into()),
             }
         },
     );
@@ -124,9 +211,9 @@ pub async fn on_connect(socket: SocketRef) {
     // so the timer is triggered through the other socket in the room (if it exists).
     socket.on(
         "disconnect_timer",
-        |socket: SocketRef, Data::<String>(room)| async move {
+        |socket: SocketRef, Data::<String>(room), state: State<AppState>| async move {
             log::info!("Player left room {:?}. Starting timer.", &room);
-            if room == "" {
+            if room.is_empty() {
                 return;
             }
             let mut timer = PeriodicTimer::started(Duration::from_secs(1));
@@ -135,10 +222,14 @@ pub async fn on_connect(socket: SocketRef) {
                 count += 1;
                 if count == 30 {
                     log::info!("Room {:?} abandoned", room.clone());
+                    state.seats.release(&room);
                     let _ = socket.within(room.clone()).emit("abandon", "").ok();
                     break;
                 } else {
-                    let length = socket.within(room.clone()).sockets().unwrap().len();
+                    let length = match socket.within(room.clone()).sockets() {
+                        Ok(sockets) => sockets.len(),
+                        Err(e) => return emit_error(&socket, anyhow::Error::new(e).into()),
+                    };
                     if length >= 2 {
                         log::info!("All players reconnected to {:?}", room);
                         return;
@@ -151,3 +242,26 @@ pub async fn on_connect(socket: SocketRef) {
         },
     );
 }
+
+// Sends the seat and the game to a player coming back to a room, the game has already started.
+async fn rejoin(socket: &SocketRef, state: &AppState, room: &str, color: Color, seat: Uuid) {
+    let id = match parse_room_code(room) {
+        Ok(id) => id,
+        Err(e) => return emit_error(socket, e),
+    };
+    socket.emit("seat", Seat::new(room, seat, color)).ok();
+    let _ = socket.join(room.to_string());
+    log::info!("{:?} rejoined room {:?} as {:?}", socket.id, room, color);
+    match state.repository.get_game(id).await {
+        Ok(chessgame) => {
+            socket.emit("start_game", create_room_dto(&chessgame)).ok();
+        }
+        Err(e) => emit_error(socket, e.into()),
+    }
+}
+
+// Socket errors carry the same body as the HTTP error responses.
+fn emit_error(socket: &SocketRef, error: ApiError) {
+    log::info!("Socket {:?} error: {}", socket.id, error.code());
+    socket.emit("error", error.body()).ok();
+}
@@ -0,0 +1,198 @@
+use crate::errors::ApiError;
+use domain::chessgame::is_known_rule;
+use domain::odds::TimeOdds;
+use domain::opponent::Opponent;
+use domain::stringtomove::string_to_move;
+use engine::strength::Strength;
+use uuid::Uuid;
+
+// Names are stored as VARCHAR(50), which counts characters rather than bytes.
+pub const MAX_NAME_LENGTH: usize = 50;
+pub const MIN_ELO: i32 = 0;
+pub const MAX_ELO: i32 = 4000;
+pub const MAX_MINUTES: u16 = 180;
+
+pub fn validate_name(name: &str) -> Result<(), ApiError> {
+    let length = name.chars().count();
+    if name.trim().is_empty() || length > MAX_NAME_LENGTH {
+        return Err(ApiError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+pub fn validate_elo(elo: i32) -> Result<(), ApiError> {
+    if !(MIN_ELO..=MAX_ELO).contains(&elo) {
+        return Err(ApiError::InvalidElo(elo));
+    }
+    Ok(())
+}
+
+pub fn validate_rule(rule_id: i32) -> Result<(), ApiError> {
+    if !is_known_rule(rule_id) {
+        return Err(ApiError::UnknownRule(rule_id));
+    }
+    Ok(())
+}
+
+// The player giving the odds can't get more time than the opponent.
+pub fn validate_time_odds(time: Option<TimeOdds>) -> Result<(), ApiError> {
+    match time {
+        Some(time)
+            if time.giver_minutes == 0
+                || time.giver_minutes > time.minutes
+                || time.minutes > MAX_MINUTES =>
+        {
+            Err(ApiError::InvalidTimeOdds(time.minutes, time.giver_minutes))
+        }
+        _ => Ok(()),
+    }
+}
+
+// A UCI engine can only be played when the server has one configured.
+pub fn validate_opponent(opponent: Opponent, has_uci_engine: bool) -> Result<(), ApiError> {
+    match opponent {
+        Opponent::Bot { level, .. } => validate_bot_level(level),
+        Opponent::Uci { .. } if !has_uci_engine => Err(ApiError::NoUciEngine),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_bot_level(level: u8) -> Result<(), ApiError> {
+    if !Strength::ALL
+        .iter()
+        .any(|strength| strength.level() == level)
+    {
+        return Err(ApiError::InvalidBotLevel(level));
+    }
+    Ok(())
+}
+
+// Admin endpoints are closed unless the server has an admin token, sent as a bearer token.
+pub fn authorize_admin(
+    authorization: Option<&str>,
+    admin_token: Option<&str>,
+) -> Result<(), ApiError> {
+    match (
+        authorization.and_then(|header| header.strip_prefix("Bearer ")),
+        admin_token,
+    ) {
+        (Some(token), Some(admin_token)) if !admin_token.is_empty() && token == admin_token => {
+            Ok(())
+        }
+        _ => Err(ApiError::NotAdmin),
+    }
+}
+
+// Only the shape of the move is checked here, whether it is allowed is up to the game. Resigning
+// needs the color of the player, bonus moves are "+undo" or "+" and two squares.
+pub fn validate_move(play_move: &str, color: Option<&str>) -> Result<(), ApiError> {
+    let valid = match play_move.strip_prefix('+') {
+        _ if play_move == "resign" => matches!(color, Some("white" | "black")),
+        Some(squares) => {
+            squares == "undo" || (squares.len() == 4 && string_to_move(squares).is_ok())
+        }
+        None => string_to_move(play_move).is_ok(),
+    };
+    if !valid {
+        return Err(ApiError::InvalidMove(play_move.to_string()));
+    }
+    Ok(())
+}
+
+pub fn validate_player(name: &str, elo: i32, rule_id: i32) -> Result<(), ApiError> {
+    validate_name(name)?;
+    validate_elo(elo)?;
+    validate_rule(rule_id)
+}
+
+pub fn parse_room_code(room: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(room).map_err(|_e| ApiError::InvalidRoomCode(room.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_must_fit_in_column() {
+        assert!(validate_name("AtoomBlom").is_ok());
+        assert!(validate_name(&"é".repeat(MAX_NAME_LENGTH)).is_ok());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LENGTH + 1)).is_err());
+        assert!(validate_name("  ").is_err());
+    }
+
+    #[test]
+    fn elo_and_rule_ranges() {
+        assert!(validate_elo(1500).is_ok());
+        assert_eq!(validate_elo(-1), Err(ApiError::InvalidElo(-1)));
+        assert!(validate_rule(0).is_ok());
+        assert_eq!(validate_rule(999), Err(ApiError::UnknownRule(999)));
+    }
+
+    #[test]
+    fn giver_gets_less_time() {
+        assert!(validate_time_odds(None).is_ok());
+        assert!(validate_time_odds(Some(TimeOdds::new(5, 3))).is_ok());
+        assert_eq!(
+            validate_time_odds(Some(TimeOdds::new(3, 5))),
+            Err(ApiError::InvalidTimeOdds(3, 5))
+        );
+        assert!(validate_time_odds(Some(TimeOdds::new(5, 0))).is_err());
+    }
+
+    #[test]
+    fn bot_levels_are_known() {
+        assert!(validate_opponent(Opponent::Hotseat, false).is_ok());
+        let bot = |level| Opponent::Bot {
+            color: chess::Color::Black,
+            level,
+        };
+        assert!(validate_opponent(bot(4), false).is_ok());
+        assert_eq!(
+            validate_opponent(bot(0), false),
+            Err(ApiError::InvalidBotLevel(0))
+        );
+        let uci = Opponent::Uci {
+            color: chess::Color::White,
+        };
+        assert!(validate_opponent(uci, true).is_ok());
+        assert_eq!(validate_opponent(uci, false), Err(ApiError::NoUciEngine));
+    }
+
+    #[test]
+    fn admin_needs_the_configured_token() {
+        assert!(authorize_admin(Some("Bearer secret"), Some("secret")).is_ok());
+        assert_eq!(
+            authorize_admin(Some("Bearer wrong"), Some("secret")),
+            Err(ApiError::NotAdmin)
+        );
+        assert!(authorize_admin(Some("secret"), Some("secret")).is_err());
+        assert!(authorize_admin(Some("Bearer "), Some("")).is_err());
+        assert!(authorize_admin(None, None).is_err());
+    }
+
+    #[test]
+    fn malformed_move_is_an_error() {
+        assert!(validate_move("e2e4", None).is_ok());
+        assert!(validate_move("e7e8q", None).is_ok());
+        assert!(validate_move("+e3e5", None).is_ok());
+        assert!(validate_move("+undo", None).is_ok());
+        assert!(validate_move("resign", Some("black")).is_ok());
+        assert_eq!(
+            validate_move("zz99", None),
+            Err(ApiError::InvalidMove("zz99".to_string()))
+        );
+        assert!(validate_move("resign", None).is_err());
+        assert!(validate_move("+e3", None).is_err());
+        assert!(validate_move("", None).is_err());
+    }
+
+    #[test]
+    fn malformed_room_code_is_an_error() {
+        assert!(parse_room_code(&Uuid::new_v4().to_string()).is_ok());
+        assert!(matches!(
+            parse_room_code("not a room"),
+            Err(ApiError::InvalidRoomCode(_))
+        ));
+    }
+}
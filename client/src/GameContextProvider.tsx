@@ -12,6 +12,8 @@ type ContextType = {
   setRoomCode: (roomCode: string) => void;
   color: Color;
   setColor: (name: Color) => void;
+  seat: string;
+  setSeat: (seat: string) => void;
 }
 
 const GameContext = createContext<ContextType | undefined>(undefined);
@@ -22,6 +24,7 @@ export const GameContextProvider = (props: Props) => {
   const [gameType, setGameType] = useState("none");
   const [roomCode, setRoomCode] = useState("");
   const [color, setColor] = useState<Color>("white");
+  const [seat, setSeat] = useState("");
   return <GameContext.Provider value={{
     gameState: gameState,
     setGameState: setGameState,
@@ -31,6 +34,8 @@ export const GameContextProvider = (props: Props) => {
     setRoomCode: setRoomCode,
     color: color,
     setColor: setColor,
+    seat: seat,
+    setSeat: setSeat,
 }}>{children}</GameContext.Provider>
 }
 
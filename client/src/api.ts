@@ -1,7 +1,7 @@
-import {Color, GameInfoType, GameState, StealoRule} from "./types";
+import {Adaptive, ApiError, Color, GameInfoType, GameState, Handicaps, Odds, OnlineGameInfoType, Opponent, StealoRule, Visibility} from "./types";
 
 // Local play
-export async function startGame(player1: string, player2: string, elo1: number, elo2: number, stealo1: number, stealo2: number) {
+export async function startGame(player1: string, player2: string, elo1: number, elo2: number, stealo1: number, stealo2: number, visibility: Visibility = {policy: "hidden"}, adaptive: Adaptive = {mode: "off"}, odds: Odds = {material: "none", time: null}, opponent: Opponent = {kind: "hotseat"}) {
     const response = await fetch("/api/startgame", {
         method: "POST",
         headers: {
@@ -14,17 +14,18 @@ export async function startGame(player1: string, player2: string, elo1: number,
             elo1: elo1,
             elo2: elo2,
             stealo1: stealo1,
-            stealo2: stealo2
+            stealo2: stealo2,
+            visibility: visibility,
+            adaptive: adaptive,
+            odds: odds,
+            opponent: opponent
         }),
     });
     if (response.ok) {
         const gamestate = await response.json();
         return gamestate as GameState;
     } else {
-        return {
-            statusCode: response.status,
-            statusText: response.statusText
-        }
+        return await response.json() as ApiError;
     }
 }
 
@@ -44,10 +45,7 @@ export async function play(move: string, color?: Color) {
         const gamestate = await response.json();
         return gamestate as GameState;
     } else {
-        return {
-            statusCode: response.status,
-            statusText: response.statusText
-        }
+        return await response.json() as ApiError;
     }
 }
 
@@ -62,6 +60,16 @@ export async function get_stealo_rules() {
     }
 }
 
+export async function get_handicaps() {
+    const response = await fetch("/api/handicaps");
+    if (response.ok) {
+        return await response.json() as Handicaps;
+    } else {
+        const empty: Handicaps = {material: [], time: []};
+        return empty;
+    }
+}
+
 export async function get_local_game_info() {
     const response = await fetch("/api/get_local_info");
     if (response.ok) {
@@ -76,7 +84,7 @@ export async function get_local_game_info() {
 }
 
 // Online play
-export async function get_game_info( roomcode: string, color: Color) {
+export async function get_game_info( roomcode: string, seat: string) {
     const response = await fetch("/api/get_game_info", {
         method: "POST",
         headers: {
@@ -85,12 +93,12 @@ export async function get_game_info( roomcode: string, color: Color) {
         },
         body: JSON.stringify({
             roomcode: roomcode,
-            color: color,
+            seat: seat,
         }),
     });
     if (response.ok) {
         const gameInfo = await response.json();
-        return gameInfo as GameInfoType;
+        return gameInfo as OnlineGameInfoType;
     } else {
         return {
             statusCode: response.status,
@@ -99,7 +107,7 @@ export async function get_game_info( roomcode: string, color: Color) {
     }
 }
 
-export async function start_online(roomcode: string, player1: string, player2: string, elo1: number, elo2:number, stealo1: number, stealo2: number) {
+export async function start_online(roomcode: string, player1: string, player2: string, elo1: number, elo2:number, stealo1: number, stealo2: number, visibility: Visibility = {policy: "hidden"}, adaptive: Adaptive = {mode: "off"}, odds: Odds = {material: "none", time: null}) {
     const response = await fetch("/api/start_online", {
         method: "POST",
         headers: {
@@ -113,16 +121,16 @@ export async function start_online(roomcode: string, player1: string, player2: s
             elo1: elo1,
             elo2: elo2,
             stealo1: stealo1,
-            stealo2: stealo2
+            stealo2: stealo2,
+            visibility: visibility,
+            adaptive: adaptive,
+            odds: odds
         }),
     });
     if (response.ok) {
         const gamestate = await response.json();
         return gamestate as GameState;
     } else {
-        return {
-            statusCode: response.status,
-            statusText: response.statusText
-        }
+        return await response.json() as ApiError;
     }
 }
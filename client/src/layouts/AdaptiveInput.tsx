@@ -0,0 +1,19 @@
+import {Adaptive} from "../types";
+
+type Props = {
+    value: Adaptive;
+    onChange: (adaptive: Adaptive) => void;
+}
+
+// Lifting the rule is the only adaptive mode offered here, milder rules can be set through the api.
+export const AdaptiveInput = (props: Props) => {
+    const { value, onChange } = props;
+
+    return ( <div className="relative px-5 py-4">
+        <label>
+            <input type="checkbox" className="mr-2" checked={value.mode != "off"}
+                   onChange={e => onChange(e.target.checked ? {mode: "lift"} : {mode: "off"})}/>
+            Lift the stronger player's rule while they are behind in material
+        </label>
+    </div>)
+}
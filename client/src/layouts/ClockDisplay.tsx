@@ -0,0 +1,39 @@
+import {useEffect, useState} from "react";
+
+type Props = {
+    white_time: number | null,
+    black_time: number | null,
+    board: string | undefined,
+}
+
+// The server sends the time left when the position was sent, the clock of the side to move
+// keeps running here until the next position arrives.
+export const ClockDisplay = (props: Props) => {
+    const { white_time, black_time, board } = props;
+    const [received, setReceived] = useState(Date.now());
+    const [now, setNow] = useState(Date.now());
+    const white_to_move = board?.split(" ")[1] === "w";
+
+    useEffect(() => {
+        setReceived(Date.now());
+        const interval = setInterval(() => setNow(Date.now()), 200);
+        return () => clearInterval(interval);
+    }, [white_time, black_time, board]);
+
+    if (white_time === null || black_time === null) {
+        return null;
+    }
+    const running = Math.max(0, now - received);
+    const white = white_to_move ? white_time - running : white_time;
+    const black = white_to_move ? black_time : black_time - running;
+
+    return (<div className="w-full flex flex-row justify-between text-2xl font-bold my-2">
+        <span>White {format_time(white)}</span>
+        <span>Black {format_time(black)}</span>
+    </div>)
+}
+
+const format_time = (ms: number) => {
+    const seconds = Math.max(0, Math.ceil(ms / 1000));
+    return Math.floor(seconds / 60) + ":" + String(seconds % 60).padStart(2, "0");
+}
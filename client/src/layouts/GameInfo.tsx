@@ -1,5 +1,6 @@
-import {Color, StealoRule} from "../types.ts";
+import {Color, ObligationProgress, reveal, Secret, StealoRule} from "../types.ts";
 import {GameButton} from "./GameButton.tsx";
+import {GuessInput} from "./GuessInput.tsx";
 
 type Props = {
     player1: string,
@@ -8,26 +9,32 @@ type Props = {
     elo2: number,
     stealo1: number,
     stealo2: number,
+    revealed1: boolean,
+    revealed2: boolean,
+    budget1: number | null,
+    budget2: number | null,
+    progress1: ObligationProgress | null,
+    progress2: ObligationProgress | null,
     result: string,
     play_move: (move: string, color: Color) => void,
 }
 
 export const GameInfo = (props: Props) => {
-    const { player1, player2, elo1, elo2, stealo1, stealo2, result, play_move } = props;
+    const { player1, player2, elo1, elo2, stealo1, stealo2, revealed1, revealed2, budget1, budget2, progress1, progress2, result, play_move } = props;
     const rules = localStorage.getItem("rules");
     const rule1 = rules? JSON.parse(rules).filter((rule: StealoRule) => {return rule.id===stealo1})[0]
         : {name: "Couldn't get rule", description: "", elo: ""};
     const rule2 = rules? JSON.parse(rules).filter((rule: StealoRule) => {return rule.id===stealo2})[0]
         : {name: "Couldn't get rule", description: "", elo: ""};
-    const stealo_css = (result != "none") ? "basis-2/12 my-2 text-xl break-words"
+    const stealo_css = (revealed: boolean) => revealed ? "basis-2/12 my-2 text-xl break-words"
         : "bg-gray-600 text-gray-600 hover:text-black hover:bg-gray-200 basis-2/12 my-2 text-xl break-words";
-    const reveal_instruction = (result == "none") ? "Hover to reveal stealo" : ""
+    const reveal_instruction = (result == "none" && !(revealed1 && revealed2)) ? "Hover to reveal stealo" : ""
 
     return (
     <div className="h-full w-full px-3 py-2 bg-gray-200 border-2 border-gray-600 rounded-lg flex flex-col">
         <div className="basis-1/12 my-2 text-2xl font-bold break-words" > {player2}  ({elo2})</div>
-        <div className={stealo_css}>{rule2.name} ({rule2.elo}): <br />
-            {rule2.description}</div>
+        <div className={stealo_css(revealed2)}>{rule2.name} ({rule2.elo}): <br />
+            {rule2.description}{budget_text(budget2)}{progress_text(progress2)}</div>
         <div className="basis-2/12 my-2 flex flex-row border-2" >
             <GameButton text={"Offer draw"} color={"black"} play_move={play_move}/>
             <GameButton text={"Resign"} color={"black"} play_move={play_move}/></div>
@@ -36,33 +43,62 @@ export const GameInfo = (props: Props) => {
             <GameButton text={"Offer draw"} color={"white"} play_move={play_move}/>
             <GameButton text={"Resign"} color={"white"} play_move={play_move}/></div>
         <div className="basis-1/12 my-2 text-2xl font-bold break-words" > {player1}  ({elo1})</div>
-        <div className={stealo_css} > {rule1.name} ({rule1.elo}): <br />
-            {rule1.description}</div>
+        <div className={stealo_css(revealed1)} > {rule1.name} ({rule1.elo}): <br />
+            {rule1.description}{budget_text(budget1)}{progress_text(progress1)}</div>
     </div>
     )
 }
 
-export const GameInfoOnline = (props: Props) => {
-    const { player1, player2, elo1, elo2, stealo1, stealo2, result } = props;
-    const elo_p1 = (elo1 != 0)? elo1 : "???";
-    const elo_p2 = (elo2 != 0 && result != "none")? elo2 : "???";
+// Rules that can only be used so often show what is left of them.
+const budget_text = (budget: number | null | undefined) => (budget === null || budget === undefined) ? "" : " (" + budget + " left)";
+
+// Rules with goals to reach by a deadline show how far the player is.
+const progress_text = (progress: ObligationProgress | null | undefined) => (progress === null || progress === undefined) ? ""
+    : " (" + progress.done + "/" + progress.total + " done, move " + progress.deadline + ")";
+
+type OnlineProps = {
+    player1: string,
+    player2: string,
+    elo1: Secret<number>,
+    elo2: Secret<number>,
+    stealo1: Secret<number>,
+    stealo2: Secret<number>,
+    budget1: Secret<number | null>,
+    budget2: Secret<number | null>,
+    progress1: Secret<ObligationProgress | null>,
+    progress2: Secret<ObligationProgress | null>,
+    points1: number,
+    points2: number,
+    guesses_left: Secret<number>,
+    guess: (rule_id: number) => void,
+}
+
+export const GameInfoOnline = (props: OnlineProps) => {
+    const { player1, player2, elo1, elo2, stealo1, stealo2, budget1, budget2, progress1, progress2, points1, points2, guesses_left, guess } = props;
+    const hidden_rule = {name: "???", description: "Rules are revealed when the game ends", elo: "???"};
+    const elo_p1 = reveal(elo1) ?? "???";
+    const elo_p2 = reveal(elo2) ?? "???";
     const rules = localStorage.getItem("rules");
-    const rule1 = rules? JSON.parse(rules).filter((rule: StealoRule) => {return rule.id===stealo1})[0]
-        : {name: "???", description: "Rules are revealed when the game ends", elo: "???"};
-    const rule2 = (rules && result != "none")? JSON.parse(rules).filter((rule: StealoRule) => {return rule.id===stealo2})[0]
-        : {name: "???", description: "Rules are revealed when the game ends", elo: "???"};
+    const find_rule = (stealo: Secret<number>) => {
+        const id = reveal(stealo);
+        return (rules && id !== undefined) ? JSON.parse(rules).filter((rule: StealoRule) => {return rule.id===id})[0]
+            : hidden_rule;
+    }
+    const rule1 = find_rule(stealo1);
+    const rule2 = find_rule(stealo2);
 
     return (
         <div className="h-full w-full px-3 py-2 bg-gray-200 border-2 border-gray-600 rounded-lg flex flex-col">
             <div className="basis-1/12 my-2 text-2xl font-bold break-words" > {player2}  ({elo_p2})</div>
             <div className="basis-2/12 my-2 text-xl break-words">{rule2.name} ({rule2.elo}): <br />
-                {rule2.description}</div>
-            <div className="basis-2/12 my-2" ></div>
-            <div className="basis-1/12 my-2 flex justify-center text-sm pt-3" ></div>
-            <div className="basis-2/12 my-2" ></div>
+                {rule2.description}{budget_text(reveal(budget2))}{progress_text(reveal(progress2))}</div>
+            <div className="basis-2/12 my-2" >{(points2 > 0) && <span>Guess points: {points2}</span>}</div>
+            <div className="basis-1/12 my-2 flex justify-center text-sm pt-3" >
+                {(reveal(stealo2) === undefined) && <GuessInput guesses_left={reveal(guesses_left) ?? 0} guess={guess}/>}</div>
+            <div className="basis-2/12 my-2" >{(points1 > 0) && <span>Guess points: {points1}</span>}</div>
             <div className="basis-1/12 my-2 text-2xl font-bold break-words" > {player1}  ({elo_p1})</div>
             <div className="basis-2/12 my-2 text-xl break-words" > {rule1.name} ({rule1.elo}): <br />
-                {rule1.description}</div>
+                {rule1.description}{budget_text(reveal(budget1))}{progress_text(reveal(progress1))}</div>
         </div>
     )
 }
\ No newline at end of file
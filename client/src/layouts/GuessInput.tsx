@@ -0,0 +1,27 @@
+import {useState} from "react";
+import {StealoRule} from "../types.ts";
+
+type Props = {
+    guesses_left: number,
+    guess: (rule_id: number) => void,
+}
+
+// Lets a player spend one of their limited guesses on the opponent's rule.
+export const GuessInput = (props: Props) => {
+    const { guesses_left, guess } = props;
+    const [rule_id, setRuleId] = useState(0);
+    const rules = localStorage.getItem("rules");
+    const data: StealoRule[] = rules ? JSON.parse(rules) : [];
+
+    if (guesses_left <= 0 || data.length == 0) {
+        return (<div className="text-sm">No guesses left</div>)
+    }
+    return (<div className="flex flex-row items-center text-sm">
+        <select className="w-3/5 bg-white rounded-lg px-2 py-1 border-2 border-gray-600" value={rule_id}
+                onChange={e => setRuleId(Number(e.target.value))}>
+            {data.map(({ id, name }) => <option key={id} value={id} label={name}></option>)}
+        </select>
+        <button className="ml-2 px-2 py-1 rounded-lg border-2 border-gray-600 bg-gray-300 hover:bg-white"
+                onClick={(e) => {e.preventDefault(); guess(rule_id)}}>Guess ({guesses_left})</button>
+    </div>)
+}
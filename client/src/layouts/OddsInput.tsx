@@ -0,0 +1,42 @@
+import {Handicaps, Odds} from "../types";
+
+type Props = {
+    handicaps: Handicaps;
+    value: Odds;
+    onChange: (odds: Odds) => void;
+}
+
+// The stronger player gives the odds, the elo of the odds counts when balancing the rules.
+export const OddsInput = (props: Props) => {
+    const { handicaps, value, onChange } = props;
+    const time_index = handicaps.time.findIndex(time => value.time !== null
+        && time.odds.minutes === value.time.minutes && time.odds.giver_minutes === value.time.giver_minutes);
+
+    return ( <div className="relative px-5 py-4 flex flex-row gap-4">
+        <label>Material odds:
+            <select className="ml-2" value={value.material}
+                    onChange={e => onChange({...value, material: e.target.value as Odds["material"]})}>
+                {handicaps.material.map(material =>
+                    <option key={material.odds} value={material.odds}>{material.name} ({material.elo})</option>)}
+            </select>
+        </label>
+        <label>Time odds:
+            <select className="ml-2" value={time_index}
+                    onChange={e => {
+                        const index = Number(e.target.value);
+                        onChange({...value, time: index < 0 ? null : handicaps.time[index].odds});
+                    }}>
+                <option value={-1}>No clock</option>
+                {handicaps.time.map((time, index) =>
+                    <option key={index} value={index}>{time.name} ({time.elo})</option>)}
+            </select>
+        </label>
+    </div>)
+}
+
+export function odds_elo(handicaps: Handicaps, odds: Odds): number {
+    const material = handicaps.material.find(material => material.odds === odds.material)?.elo ?? 0;
+    const time = handicaps.time.find(time => odds.time !== null && time.odds.minutes === odds.time.minutes
+        && time.odds.giver_minutes === odds.time.giver_minutes)?.elo ?? 0;
+    return material + time;
+}
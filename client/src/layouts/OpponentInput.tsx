@@ -0,0 +1,44 @@
+import {Color, Opponent} from "../types";
+
+type Props = {
+    value: Opponent;
+    onChange: (opponent: Opponent) => void;
+}
+
+const levels = [
+    {level: 1, name: "Beginner"},
+    {level: 2, name: "Casual"},
+    {level: 3, name: "Club"},
+    {level: 4, name: "Expert"},
+];
+
+// The bot plays the side of the player it replaces, under that player's rule. The UCI engine is
+// only there when the server has one configured, otherwise starting the game is refused.
+export const OpponentInput = (props: Props) => {
+    const { value, onChange } = props;
+    const side: Color = value.kind == "hotseat" ? "black" : value.side;
+    const level = value.kind == "bot" ? value.level : 2;
+    const strength = value.kind == "uci" ? "uci" : String(level);
+    const withSide = (side: Color, strength: string): Opponent =>
+        strength == "uci" ? {kind: "uci", side: side} : {kind: "bot", side: side, level: Number(strength)};
+
+    return ( <div className="relative px-5 py-4">
+        <label>
+            <input type="checkbox" className="mr-2" checked={value.kind != "hotseat"}
+                   onChange={e => onChange(e.target.checked ? withSide(side, strength) : {kind: "hotseat"})}/>
+            Play against the computer
+        </label>
+        {value.kind != "hotseat" && <div className="mt-2">
+            <select className="mr-2 border-2" value={side}
+                    onChange={e => onChange(withSide(e.target.value as Color, strength))}>
+                <option value="white">Computer plays white (player 1)</option>
+                <option value="black">Computer plays black (player 2)</option>
+            </select>
+            <select className="border-2" value={strength}
+                    onChange={e => onChange(withSide(side, e.target.value))}>
+                {levels.map(({level, name}) => <option key={level} value={String(level)}>{name}</option>)}
+                <option value="uci">UCI engine</option>
+            </select>
+        </div>}
+    </div>)
+}
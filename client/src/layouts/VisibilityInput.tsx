@@ -0,0 +1,34 @@
+import {Visibility} from "../types";
+
+type Props = {
+    value: Visibility;
+    onChange: (visibility: Visibility) => void;
+}
+
+export const VisibilityInput = (props: Props) => {
+    const { value, onChange } = props;
+    const moves = (value.policy == "reveal_after") ? value.moves : 10;
+
+    function select_policy(policy: string) {
+        switch (policy) {
+            case "open": {onChange({policy: "open"}); break;}
+            case "reveal_after": {onChange({policy: "reveal_after", moves: moves}); break;}
+            case "reveal_on_forbidden_attempt": {onChange({policy: "reveal_on_forbidden_attempt"}); break;}
+            default: {onChange({policy: "hidden"});}
+        }
+    }
+
+    return ( <div className="relative px-5 py-4">
+        <span>Rules are revealed: </span>
+        <select className="bg-white rounded-lg px-4 py-2 border-2 border-gray-600" value={value.policy}
+                onChange={e => select_policy(e.target.value)}>
+            <option value="hidden">When the game ends</option>
+            <option value="open">From the start</option>
+            <option value="reveal_after">After a number of moves</option>
+            <option value="reveal_on_forbidden_attempt">When a player tries a forbidden move</option>
+        </select>
+        {(value.policy == "reveal_after") &&
+            <input className="w-20 ml-2 bg-white rounded-lg px-2 py-2 border-2 border-gray-600" type="number" min={1}
+                   value={moves} onChange={e => onChange({policy: "reveal_after", moves: Number(e.target.value)})}/>}
+    </div>)
+}
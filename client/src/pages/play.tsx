@@ -2,17 +2,21 @@ import { Chessboard } from "react-chessboard"
 import { useGameContext} from "../GameContextProvider.tsx"
 import {Square, Piece} from "react-chessboard/dist/chessboard/types";
 import {get_local_game_info, play} from "../api"
-import {isGameState, isGameInfoType, GameInfoType, Color} from "../types.ts";
-import {format_promotion_piece} from "../shared_functions.ts";
+import {isApiError, isGameState, isGameInfoType, GameInfoType, Color} from "../types.ts";
+import {format_promotion_piece, with_bonus} from "../shared_functions.ts";
 import {useEffect, useState} from "react";
+import {ClockDisplay} from "../layouts/ClockDisplay.tsx";
 import {GameInfo} from "../layouts/GameInfo.tsx";
 
 export const Play = () => {
     const {gameState, setGameState} = useGameContext();
     const board = gameState?.board;
     const moves = gameState?.moves;
+    const bonus_moves = gameState?.bonus_moves ?? [];
     const result = gameState?.result;
-    const initialInfo: GameInfoType = {  white:"", black: "", white_elo: 0, black_elo: 0, white_stealo: 0, black_stealo: 0 }
+    const initialInfo: GameInfoType = {  white:"", black: "", white_elo: 0, black_elo: 0, white_stealo: 0, black_stealo: 0,
+        white_stealo_revealed: false, black_stealo_revealed: false, white_guess_points: 0, black_guess_points: 0,
+        white_budget: null, black_budget: null, white_progress: null, black_progress: null }
     const [gameInfo, setGameInfo] = useState<GameInfoType>(initialInfo)
     let text = ""
     let drag_pawn: boolean = false; // Used to check if a pawn is being promoted this move.
@@ -38,16 +42,14 @@ export const Play = () => {
     async function move(move: string, color?: Color) {
         if (!moves) {
             return false;
-        } else if (moves.includes(move) || move == "resign" ) {
+        } else {
+            // Forbidden moves are sent too, the server may reveal the rule that forbids them.
             const new_position = await play(move, color)
-            console.log(new_position);
-            if (isGameState(new_position)) {
+            if (!isApiError(new_position) && isGameState(new_position)) {
                 setGameState(new_position);
-                return true;
+                return moves.includes(move) || bonus_moves.includes(move) || move == "resign";
             }
             return false;
-        } else {
-            return false;
         }
     }
 
@@ -60,9 +62,10 @@ export const Play = () => {
         }
     }
 
+    // Rules can be revealed during the game, depending on the game's visibility policy.
     useEffect(() => {
         set_game_info()
-    },[])
+    },[gameState])
 
 
     //<div className="text-2xl text-gray-700 font-semibold">{ player2 }</div>
@@ -73,15 +76,24 @@ export const Play = () => {
             <div className="w-2/3 flex flex-col items-center justify-center">
                 <Chessboard position={board} onPieceDragBegin={(piece) => is_piece_a_pawn(piece)}
                             onPieceDrop={(sourceSquare, targetSquare, piece) => {
-                                move(format_move(sourceSquare, targetSquare, piece));
+                                move(with_bonus(format_move(sourceSquare, targetSquare, piece), bonus_moves));
                                 return false
                             }}
                             animationDuration={80}/>
+                <ClockDisplay white_time={gameState?.white_time ?? null} black_time={gameState?.black_time ?? null}
+                              board={board}/>
+                {bonus_moves.includes("+undo") &&
+                    <button className="mt-2 text-xl rounded-full bg-slate-700 text-white p-2 hover:bg-slate-500"
+                            onClick={() => move("+undo")}>Take back</button>}
             </div>
             <div className="w-1/3">
                 <GameInfo player1={gameInfo.white} player2={gameInfo.black}
                           elo1={gameInfo.white_elo} elo2={gameInfo.black_elo}
-                          stealo1={gameInfo.white_stealo} stealo2={gameInfo.black_stealo} result={result} play_move={move}/>
+                          stealo1={gameInfo.white_stealo} stealo2={gameInfo.black_stealo}
+                          revealed1={gameInfo.white_stealo_revealed} revealed2={gameInfo.black_stealo_revealed}
+                          budget1={gameInfo.white_budget} budget2={gameInfo.black_budget}
+                          progress1={gameInfo.white_progress} progress2={gameInfo.black_progress}
+                          result={result} play_move={move}/>
             </div>
         </div>)
     } else if (result != undefined) {
@@ -110,7 +122,11 @@ export const Play = () => {
                 <div className="w-1/3">
                     <GameInfo player1={gameInfo.white} player2={gameInfo.black}
                               elo1={gameInfo.white_elo} elo2={gameInfo.black_elo}
-                              stealo1={gameInfo.white_stealo} stealo2={gameInfo.black_stealo} result={result} play_move={move}/>
+                              stealo1={gameInfo.white_stealo} stealo2={gameInfo.black_stealo}
+                              revealed1={true} revealed2={true}
+                              budget1={gameInfo.white_budget} budget2={gameInfo.black_budget}
+                              progress1={gameInfo.white_progress} progress2={gameInfo.black_progress}
+                              result={result} play_move={move}/>
                 </div>
             </div>
         </div>)
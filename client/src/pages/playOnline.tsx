@@ -2,33 +2,44 @@ import {useState, useEffect, useContext} from "react";
 import {Chessboard} from "react-chessboard";
 import {useGameContext} from "../GameContextProvider.tsx";
 import {get_game_info} from "../api.ts";
-import {isGameState, GameInfoType, OnlineMove, isGameInfoType, Color} from "../types.ts";
+import {isGameState, OnlineGameInfoType, OnlineMove, isOnlineGameInfoType, OnlineGuess, GuessResult, reveal} from "../types.ts";
 import {Piece, Square} from "react-chessboard/dist/chessboard/types";
-import {format_promotion_piece} from "../shared_functions.ts";
+import {format_promotion_piece, verify_commitment, with_bonus} from "../shared_functions.ts";
 import {SocketContext} from "../SocketContext.tsx";
+import {ClockDisplay} from "../layouts/ClockDisplay.tsx";
 import {GameInfoOnline} from "../layouts/GameInfo.tsx";
 
 export const Play = () => {
     const websocket = useContext(SocketContext);
-    const {gameState, setGameState, roomCode, color} = useGameContext();
+    const {gameState, setGameState, roomCode, color, seat} = useGameContext();
     const board = gameState?.board;
-    const initialInfo: GameInfoType = {  white:"", black: "", white_elo: 0, black_elo: 0, white_stealo: 0, black_stealo: 0 }
-    const [gameInfo, setGameInfo] = useState<GameInfoType>(initialInfo)
+    const initialInfo: OnlineGameInfoType = {  white:"", black: "", white_elo: "hidden", black_elo: "hidden", white_stealo: "hidden", black_stealo: "hidden",
+        white_guesses: "hidden", black_guesses: "hidden", white_guess_points: 0, black_guess_points: 0, guesses_left: "hidden",
+        white_budget: "hidden", black_budget: "hidden", white_progress: "hidden", black_progress: "hidden", bonus_moves: "hidden", commitment: null, nonce: "hidden" }
+    const [gameInfo, setGameInfo] = useState<OnlineGameInfoType>(initialInfo)
     const player1 = (color === "white") ? gameInfo.white : gameInfo.black;
     const player2 = (color === "white") ? gameInfo.black : gameInfo.white;
     const elo1 = (color === "white") ? gameInfo.white_elo : gameInfo.black_elo;
     const elo2 = (color === "white") ? gameInfo.black_elo : gameInfo.white_elo;
     const stealo1 = (color === "white") ? gameInfo.white_stealo : gameInfo.black_stealo;
     const stealo2 = (color === "white") ? gameInfo.black_stealo : gameInfo.white_stealo;
+    const budget1 = (color === "white") ? gameInfo.white_budget : gameInfo.black_budget;
+    const budget2 = (color === "white") ? gameInfo.black_budget : gameInfo.white_budget;
+    const progress1 = (color === "white") ? gameInfo.white_progress : gameInfo.black_progress;
+    const progress2 = (color === "white") ? gameInfo.black_progress : gameInfo.white_progress;
+    const points1 = (color === "white") ? gameInfo.white_guess_points : gameInfo.black_guess_points;
+    const points2 = (color === "white") ? gameInfo.black_guess_points : gameInfo.white_guess_points;
     const moves = gameState ? gameState.moves : [];
+    // The room only gets bonus moves once the rule is revealed, the game info has our own.
+    const bonus_moves = reveal(gameInfo.bonus_moves) ?? [];
     const result = gameState ? gameState.result : "none";
     const draggable = (color === "white" && board?.split(" ")[1] === 'w') || (color === "black" && board?.split(" ")[1] === 'b');
     let drag_pawn: boolean = false; // Used to check if a pawn is being promoted this move.
     let resultText = "";
 
     const set_game_info = async() => {
-        const game_info = await get_game_info(roomCode, color);
-        if (isGameInfoType(game_info)) {
+        const game_info = await get_game_info(roomCode, seat);
+        if (isOnlineGameInfoType(game_info)) {
             setGameInfo(game_info);
         } else {
             alert("Everything is on fire.")
@@ -39,16 +50,19 @@ export const Play = () => {
         if (!moves) {
             return false;
         }
-        else if (moves.includes(move)) {
-            const online_move: OnlineMove = { roomcode: roomCode, play_move: move}
-            websocket.emit("move", online_move)
-            return false;
-        }
         else {
+            // Forbidden moves are sent too, the server may reveal the rule that forbids them.
+            const online_move: OnlineMove = { roomcode: roomCode, seat: seat, play_move: move}
+            websocket.emit("move", online_move)
             return false;
         }
     }
 
+    function guess(rule_id: number) {
+        const online_guess: OnlineGuess = { roomcode: roomCode, seat: seat, rule_id: rule_id }
+        websocket.emit("guess", online_guess)
+    }
+
     function format_move(source: Square, target: Square, piece: Piece) {
         let move = source + target;
         if ((source[1]=='7' && target[1]=='8') || (source[1]=='2' && target[1]=='1')) {
@@ -63,20 +77,32 @@ export const Play = () => {
         drag_pawn = piece == 'wP' || piece == 'bP';
     }
 
-    function filler_play_move(_move: string, _color: Color) {}
-
-    // Dependency on result to request game info again once the game is finished.
-    // Server send full game info on a finished game and partial info on an ongoing one.
+    // Dependency on the board, result and the revealed rules to request game info again once any changes.
+    // Server sends game info according to the game's visibility policy, the board for the rule budgets.
+    const revealed = JSON.stringify([gameState?.white_stealo, gameState?.black_stealo]);
     useEffect(() => {
         set_game_info()
-    }, [result])
+    }, [board, result, revealed])
+
+    // Once the game has ended the rules and nonce are revealed, so the commitment can be checked.
+    // It is checked against the copy from the start of the game, not the one the server sends at the end.
+    const [commitment] = useState(gameState?.commitment ?? null);
+    const [verified, setVerified] = useState<boolean | undefined>(undefined);
+    useEffect(() => {
+        const white_stealo = reveal(gameInfo.white_stealo);
+        const black_stealo = reveal(gameInfo.black_stealo);
+        const nonce = reveal(gameInfo.nonce);
+        if (commitment && white_stealo !== undefined && black_stealo !== undefined && nonce !== undefined) {
+            verify_commitment(commitment, white_stealo, black_stealo, nonce).then(setVerified);
+        }
+    }, [gameInfo])
 
     useEffect( () =>{
         websocket.on("connected", () => {
-            if ( roomCode != "" ) { websocket.emit("reconnected", roomCode) }
+            if ( roomCode != "" ) { websocket.emit("reconnected", {room: roomCode, seat: seat}) }
         });
         websocket.on("disconnected", () => { websocket.emit("disconnect_timer", roomCode) });
-    }, [websocket, roomCode])
+    }, [websocket, roomCode, seat])
 
     useEffect( () => {
         websocket.on("sync", (arg) => {
@@ -84,6 +110,10 @@ export const Play = () => {
                 setGameState(arg);
             }
         });
+        websocket.on("guess_result", (arg: GuessResult) => {
+            alert(arg.correct ? "Correct! That is your opponent's rule." : "Wrong guess, " + arg.guesses_left + " left.");
+            set_game_info();
+        });
         websocket.on("abandon", () => {
             if (result == "none") {alert("Opponent abandoned the game"); location.reload()}
         });
@@ -96,16 +126,22 @@ export const Play = () => {
                     <Chessboard position={board} boardOrientation={color} arePiecesDraggable={draggable}
                                 animationDuration={80} onPieceDragBegin={(piece) => is_piece_a_pawn(piece)}
                                 onPieceDrop={(sourceSquare, targetSquare, piece) => {
-                                    const move = format_move(sourceSquare, targetSquare, piece);
+                                    const move = with_bonus(format_move(sourceSquare, targetSquare, piece), bonus_moves);
                                     play_move(move);
                                     return moves.includes(move)
                                 }}
                     />
+                    <ClockDisplay white_time={gameState?.white_time ?? null} black_time={gameState?.black_time ?? null}
+                                  board={board}/>
+                    {draggable && bonus_moves.includes("+undo") &&
+                        <button className="mt-2 text-xl rounded-full bg-slate-700 text-white p-2 hover:bg-slate-500"
+                                onClick={() => play_move("+undo")}>Take back</button>}
                 </div>
                 <div className="w-1/3">
                     <GameInfoOnline player1={player1} player2={player2}
                               elo1={elo1} elo2={elo2}
-                              stealo1={stealo1} stealo2={stealo2} result={result} play_move={filler_play_move}
+                              stealo1={stealo1} stealo2={stealo2} budget1={budget1} budget2={budget2} progress1={progress1} progress2={progress2} points1={points1} points2={points2}
+                              guesses_left={gameInfo.guesses_left} guess={guess}
                     />
                 </div>
             </div>
@@ -125,6 +161,8 @@ export const Play = () => {
              bg-gray-200 rounded-lg bg-opacity-50 text-center"> { resultText }
                 <button className="text-xl rounded-full bg-slate-700 text-white bg-opacity-100 p-2 w-full hover:bg-slate-500"
                         onClick={() => {window.location.reload()}}>New game</button>
+                {(verified !== undefined) && <div className="text-base font-normal pt-2">
+                    {verified ? "Rules match the commitment made at the start" : "Rules do NOT match the commitment!"}</div>}
             </div>
                 <div className="w-3/5 mx-auto mt-10 flex flex-row">
                     <div className="w-2/3 flex flex-col items-center justify-center">
@@ -133,7 +171,8 @@ export const Play = () => {
                     <div className="w-1/3">
                         <GameInfoOnline player1={player1} player2={player2}
                                   elo1={elo1} elo2={elo2}
-                                  stealo1={stealo1} stealo2={stealo2} result={result} play_move={filler_play_move}/>
+                                  stealo1={stealo1} stealo2={stealo2} budget1={budget1} budget2={budget2} progress1={progress1} progress2={progress2} points1={points1} points2={points2}
+                                  guesses_left={gameInfo.guesses_left} guess={guess}/>
                     </div>
                 </div>
             </div>
@@ -1,9 +1,13 @@
 import { useEffect, useState } from "react";
 import {useGameContext} from "../GameContextProvider.tsx";
-import { StealoRule, isGameState } from "../types";
+import { Adaptive, Handicaps, Odds, Opponent, StealoRule, Visibility, isApiError, isGameState } from "../types";
 import { FormInput } from "../layouts/FormInput";
-import {startGame, get_stealo_rules} from "../api";
+import {startGame, get_stealo_rules, get_handicaps} from "../api";
 import { StealoInput } from "../layouts/StealoInput";
+import { VisibilityInput } from "../layouts/VisibilityInput";
+import { AdaptiveInput } from "../layouts/AdaptiveInput";
+import { OddsInput, odds_elo } from "../layouts/OddsInput";
+import { OpponentInput } from "../layouts/OpponentInput";
 import {random_stealo} from "../shared_functions.ts";
 
 
@@ -19,6 +23,11 @@ export const Start = () => {
     const [description1, setDescription1] = useState("Good old normal chess");
     const [description2, setDescription2] = useState("Good old normal chess");
     const [rules, setRules] = useState<StealoRule[]>([]);
+    const [visibility, setVisibility] = useState<Visibility>({policy: "hidden"});
+    const [adaptive, setAdaptive] = useState<Adaptive>({mode: "off"});
+    const [odds, setOdds] = useState<Odds>({material: "none", time: null});
+    const [opponent, setOpponent] = useState<Opponent>({kind: "hotseat"});
+    const [handicaps, setHandicaps] = useState<Handicaps>({material: [], time: []});
     const valid = player1 !== "" && player2 !== "" && player1 !== player2;
     const validElo = (Number(elo1) > 0 || elo1 == "") && (Number(elo2) > 0 || elo2 =="") && ((elo1 !== "" && elo2 !== "") || (elo1 === "" && elo2 === ""));
     
@@ -35,7 +44,7 @@ export const Start = () => {
             alert("Please enter both player elos first")
             return
         }
-        const [rule1, rule2] = random_stealo(elo1, elo2, stealo1, stealo2, rules);
+        const [rule1, rule2] = random_stealo(elo1, elo2, stealo1, stealo2, rules, odds_elo(handicaps, odds));
         setStealo1(rule1);
         setStealo2(rule2);
         setDescription1(get_description(rule1));
@@ -45,14 +54,13 @@ export const Start = () => {
     const start_game = async () => {
         const elo_white = (isNaN(Number(elo1))) ? 0 : Number(elo1);
         const elo_black = (isNaN(Number(elo2))) ? 0 : Number(elo2);
-        const result = await startGame(player1, player2, elo_white, elo_black, stealo1, stealo2)
-        if (isGameState(result)) {
+        const result = await startGame(player1, player2, elo_white, elo_black, stealo1, stealo2, visibility, adaptive, odds, opponent)
+        if (isApiError(result)) {
+            alert(result.message)
+        } else if (isGameState(result)) {
             setGameState(result);
             setGameType("local");
         }
-        else {
-            alert("AAAAAAAAAAAAAAH")
-        }
     }
 
     function get_description(id: number): string {
@@ -61,6 +69,7 @@ export const Start = () => {
     }
 
     useEffect(() => {
+        get_handicaps().then(setHandicaps);
         const rules_storage = localStorage.getItem('rules');
         if (rules_storage) {
             const rules_sorted = JSON.parse(rules_storage).sort((a: StealoRule, b: StealoRule) => a.elo-b.elo);
@@ -137,6 +146,10 @@ export const Start = () => {
                             value={stealo2}
                             />
                     </div>
+                    <VisibilityInput value={visibility} onChange={setVisibility}/>
+                    <AdaptiveInput value={adaptive} onChange={setAdaptive}/>
+                    <OddsInput handicaps={handicaps} value={odds} onChange={setOdds}/>
+                    <OpponentInput value={opponent} onChange={setOpponent}/>
                     <div className="flex items-center justify-center">
                         <button className="px-5 py-1 mb-5 mt-3 rounded-lg text-xl border-gray-600 border-2 bg-gray-300 hover:bg-white"
                         disabled={!valid} onClick={(event) => { event.preventDefault(); start_game()}}>Start game</button>
@@ -1,21 +1,23 @@
 import {useGameContext} from "../GameContextProvider.tsx";
 import {FormInput} from "../layouts/FormInput.tsx";
 import {StealoInput} from "../layouts/StealoInput.tsx";
+import {VisibilityInput} from "../layouts/VisibilityInput.tsx";
 import {useContext, useEffect, useState} from "react";
-import {GameState, isGameState, StealoRule} from "../types.ts";
+import {GameState, isApiError, isGameState, Seat, StealoRule, Visibility} from "../types.ts";
 import {get_stealo_rules, start_online} from "../api.ts";
 import {nanoid} from "nanoid";
-import {random_stealo} from "../shared_functions.ts";
+import {previous_seat, random_stealo, remember_seat} from "../shared_functions.ts";
 import {SocketContext} from "../SocketContext.tsx";
 
 export const Start = () => {
     const websocket = useContext(SocketContext);
-    const { setGameState, roomCode, setRoomCode, setGameType, setColor } = useGameContext();
+    const { setGameState, roomCode, setRoomCode, setGameType, setColor, setSeat } = useGameContext();
     const [waitForPlayerTwo, setWaitForPlayerTwo] = useState(false);
     const [rules, setRules] = useState<StealoRule[]>([]);
     const [player, setPlayer] = useState("");
     const [elo, setElo] = useState("");
     const [stealo, setStealo] = useState(0);
+    const [visibility, setVisibility] = useState<Visibility>({policy: "hidden"});
     const [description1, setDescription1] = useState("Good old normal chess");
     const [code, setCode] = useState("");
     const valid = ( player != "")
@@ -46,7 +48,7 @@ export const Start = () => {
     function join_room() {
         setColor("black")
         setRoomCode(code);
-        websocket.emit("join", {room: code, name: player, elo: elo, stealo: stealo});
+        websocket.emit("join", {room: code, name: player, elo: elo, stealo: stealo, seat: previous_seat(code)});
     }
 
     function start_game(result: GameState) {
@@ -65,24 +67,30 @@ export const Start = () => {
                 const [stealo1, stealo2]: [number, number] = random_stealo(elo, arg.elo, stealo, arg.stealo, rules);
                 const elo1 = (isNaN(Number(elo))) ? 0 : Number(elo);
                 const elo2 = (isNaN(Number(arg.elo))) ? 0 : Number(arg.elo);
-                const result = await start_online(roomCode, player, arg.name, elo1, elo2, stealo1, stealo2);
-                if (isGameState(result)) {
+                const result = await start_online(roomCode, player, arg.name, elo1, elo2, stealo1, stealo2, visibility);
+                if (isApiError(result)) {
+                    alert(result.message)
+                } else if (isGameState(result)) {
                     setGameState(result);
                     start_game(result);
-                } else {
-                    alert("Something went wrong ")
                 }
             }
         })
-    },[websocket, player, roomCode, waitForPlayerTwo]);
+    },[websocket, player, roomCode, waitForPlayerTwo, visibility]);
 
     useEffect( () => {
+        websocket.on("seat", (arg: Seat) => {
+            remember_seat(arg);
+            setSeat(arg.token);
+            setColor(arg.color);
+        });
         websocket.on("join",() => { setWaitForPlayerTwo(true)});
         websocket.on("room_not_found", () => { alert("Game not found. You can create a new game with the 'Start game' button")})
         websocket.on("full", () => alert("Game already has 2 players"));
         websocket.on("leave", () => {setWaitForPlayerTwo(false)});
         websocket.on("start_game", (arg) => {
             if (isGameState(arg)) {
+                setGameType("online");
                 setGameState(arg);
             }
         })
@@ -122,6 +130,7 @@ export const Start = () => {
                             value ={stealo}
                         />
                     </div>
+                    <VisibilityInput value={visibility} onChange={setVisibility}/>
 
                     <div className="my-4 px-5 flex items-center justify-center">
                         <FormInput
@@ -1,12 +1,13 @@
 import {Piece} from "react-chessboard/dist/chessboard/types";
-import {StealoRule} from "./types.ts";
+import {Seat, StealoRule} from "./types.ts";
 
 
-export function random_stealo(elo1: string, elo2: string, stealo1: number, stealo2: number, rules: StealoRule[]): [number, number] {
+// Material and time odds already make up for part of the rating gap, given as odds_elo.
+export function random_stealo(elo1: string, elo2: string, stealo1: number, stealo2: number, rules: StealoRule[], odds_elo: number = 0): [number, number] {
     if (!Number(elo1) || !Number(elo2)) {return [stealo1, stealo2]}
     const e1 = (Number(elo1) >= Number(elo2)) ? Number(elo1) : Number(elo2);
     const e2 = (Number(elo1) >= Number(elo2)) ? Number(elo2) : Number(elo1);
-    const difference = e1 - e2;
+    const difference = Math.max(0, e1 - e2 - odds_elo);
     let rules_filtered = rules.filter(rule => rule.elo >= difference);
     if (rules_filtered.length == 0) {
         rules_filtered = rules.filter(rule => rule.elo == 1500);
@@ -20,6 +21,28 @@ export function random_stealo(elo1: string, elo2: string, stealo1: number, steal
     return (Number(elo1) == e1) ? [rule1.id, rule2.id]: [rule2.id, rule1.id];
 }
 
+// Checks the commitment published at the start of the game against the rules and nonce revealed at the end.
+export async function verify_commitment(commitment: string, white_stealo: number, black_stealo: number, nonce: string): Promise<boolean> {
+    const data = new TextEncoder().encode(white_stealo + ":" + black_stealo + ":" + nonce);
+    const digest = await crypto.subtle.digest("SHA-256", data);
+    const hex = Array.from(new Uint8Array(digest)).map(b => b.toString(16).padStart(2, "0")).join("");
+    return hex == commitment.toLowerCase();
+}
+
+// Seat tokens survive a reload of the tab, so the player can rejoin the room with the same seat.
+export function remember_seat(seat: Seat) {
+    sessionStorage.setItem("seat:" + seat.room, seat.token);
+}
+
+export function previous_seat(room: string): string | undefined {
+    return sessionStorage.getItem("seat:" + room) ?? undefined;
+}
+
+// Bonus moves are sent with a "+" in front, so the server doesn't mistake them for normal moves.
+export function with_bonus(move: string, bonus_moves: string[]): string {
+    return bonus_moves.includes("+" + move) ? "+" + move : move;
+}
+
 export function format_promotion_piece(piece: Piece): string {
     switch (piece) {
         case "wN":
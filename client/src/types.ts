@@ -1,15 +1,80 @@
 export type GameState = {
     board: string;
     moves: string[];
+    bonus_moves: string[];
     result: "none" | "white" | "black" | "draw"
+    white_stealo: Secret<number>;
+    black_stealo: Secret<number>;
+    white_budget: Secret<number | null>;
+    black_budget: Secret<number | null>;
+    white_progress: Secret<ObligationProgress | null>;
+    black_progress: Secret<ObligationProgress | null>;
+    commitment: string | null;
+    nonce: Secret<string>;
+    handicap_events: HandicapEvent[];
+    white_time: number | null;
+    black_time: number | null;
 }
 
+// Classic handicaps, given by the stronger player unless the giver is set.
+export type Material = "none" | "pawn_and_move" | "knight" | "rook" | "queen"
+
+export type TimeOdds = {
+    minutes: number,
+    giver_minutes: number
+}
+
+export type Odds = {
+    material: Material,
+    time: TimeOdds | null,
+    giver?: Color
+}
+
+export type Handicaps = {
+    material: { odds: Material, name: string, elo: number }[],
+    time: { odds: TimeOdds, name: string, elo: number }[]
+}
+
+// The adaptive handicap eases the stronger player's rule while they are behind in material.
+export type Adaptive =
+    { mode: "off" }
+    | { mode: "lift" }
+    | { mode: "milder", rule_id: number }
+
+// Local games are played at one screen, or against the computer playing one side.
+export type Opponent =
+    { kind: "hotseat" }
+    | { kind: "bot", side: Color, level: number }
+    | { kind: "uci", side: Color }
+
+export type HandicapEvent = {
+    ply: number,
+    eased: boolean
+}
+
+// When the rules are revealed to the opponent, decided per game.
+export type Visibility =
+    { policy: "open" }
+    | { policy: "hidden" }
+    | { policy: "reveal_after", moves: number }
+    | { policy: "reveal_on_forbidden_attempt" }
+
 export type Color = "white" | "black"
 
 export function isGameState(gameState: unknown): gameState is GameState {
     return (gameState as GameState) !== undefined;
 }
 
+// Body of every error response, and the payload of the socket "error" event.
+export type ApiError = {
+    code: string,
+    message: string
+}
+
+export function isApiError(error: unknown): error is ApiError {
+    return typeof error === "object" && error !== null && "code" in error && "message" in error;
+}
+
 export type StealoRule = {
     id: number;
     name: string;
@@ -19,6 +84,7 @@ export type StealoRule = {
 
 export type OnlineMove = {
     roomcode: string,
+    seat: string,
     play_move: string
 }
 
@@ -29,8 +95,74 @@ export type GameInfoType = {
     black_elo: number,
     white_stealo: number,
     black_stealo: number,
+    white_stealo_revealed: boolean,
+    black_stealo_revealed: boolean,
+    white_guess_points: number,
+    black_guess_points: number,
+    white_budget: number | null,
+    black_budget: number | null,
+    white_progress: ObligationProgress | null,
+    black_progress: ObligationProgress | null,
+}
+
+// Goals of an obligation rule done so far, due by the player's move with the deadline's number.
+export type ObligationProgress = {
+    done: number,
+    total: number,
+    deadline: number,
 }
 
 export function isGameInfoType(gameInfo: unknown): gameInfo is GameInfoType {
     return (gameInfo as GameInfoType) !== undefined;
+}
+
+// Online games hide the opponent's info until the server decides it can be revealed.
+export type Secret<T> = { revealed: T } | "hidden"
+
+export function reveal<T>(secret: Secret<T>): T | undefined {
+    return (secret === "hidden") ? undefined : secret.revealed;
+}
+
+export type OnlineGameInfoType = {
+    white: string,
+    black: string,
+    white_elo: Secret<number>,
+    black_elo: Secret<number>,
+    white_stealo: Secret<number>,
+    black_stealo: Secret<number>,
+    white_guesses: Secret<number[]>,
+    black_guesses: Secret<number[]>,
+    white_guess_points: number,
+    black_guess_points: number,
+    guesses_left: Secret<number>,
+    white_budget: Secret<number | null>,
+    black_budget: Secret<number | null>,
+    white_progress: Secret<ObligationProgress | null>,
+    black_progress: Secret<ObligationProgress | null>,
+    bonus_moves: Secret<string[]>,
+    commitment: string | null,
+    nonce: Secret<string>,
+}
+
+export type OnlineGuess = {
+    roomcode: string,
+    seat: string,
+    rule_id: number
+}
+
+export type GuessResult = {
+    correct: boolean,
+    guesses_left: number,
+    game: GameState
+}
+
+// The server binds a socket to a seat with this token, kept per room so a reload can reclaim it.
+export type Seat = {
+    room: string,
+    token: string,
+    color: Color
+}
+
+export function isOnlineGameInfoType(gameInfo: unknown): gameInfo is OnlineGameInfoType {
+    return (gameInfo as OnlineGameInfoType) !== undefined;
 }
\ No newline at end of file
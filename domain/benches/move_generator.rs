@@ -0,0 +1,55 @@
+use chess::{Game, MoveGen};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use domain::filters::context::FilterContext;
+use domain::move_generator::generate_moves;
+use std::hint::black_box;
+
+// A rule of every kind, from simple move filters to the ones that replay the whole history.
+const RULES: [i32; 10] = [0, 3, 24, 39, 55, 63, 64, 69, 80, 86];
+
+// A long game that is the same on every run, the moves are picked by their index.
+fn long_game(plies: usize) -> Vec<Game> {
+    let mut games = vec![Game::new()];
+    for ply in 0..plies {
+        let mut game = games[ply].clone();
+        let moves: Vec<_> = MoveGen::new_legal(&game.current_position()).collect();
+        if moves.is_empty() {
+            break;
+        }
+        game.make_move(moves[(ply * 7) % moves.len()]);
+        games.push(game);
+    }
+    games
+}
+
+// Every position of the game is asked for once, like the server does while the game is played.
+fn whole_game(c: &mut Criterion) {
+    let games = long_game(160);
+    let mut group = c.benchmark_group("whole_game");
+    for rule in RULES {
+        group.bench_with_input(BenchmarkId::from_parameter(rule), &rule, |b, rule| {
+            b.iter(|| {
+                for game in &games {
+                    black_box(generate_moves(*rule, &FilterContext::from_game(game)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+// Only the last position, where the history is the longest.
+fn last_position(c: &mut Criterion) {
+    let games = long_game(160);
+    let game = games.last().unwrap();
+    let mut group = c.benchmark_group("last_position");
+    for rule in RULES {
+        group.bench_with_input(BenchmarkId::from_parameter(rule), &rule, |b, rule| {
+            b.iter(|| black_box(generate_moves(*rule, &FilterContext::from_game(game))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, whole_game, last_position);
+criterion_main!(benches);
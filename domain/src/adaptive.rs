@@ -0,0 +1,129 @@
+use crate::filters::history::History;
+use crate::filters::material::material;
+use chess::{Board, Color};
+
+// Eases the stronger player's rule while they are behind in material, so one lost piece
+// doesn't decide the game. The rule is back as soon as they have caught up again.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Adaptive {
+    #[default]
+    Off,
+    // Normal chess while behind.
+    Lift,
+    // This rule instead while behind.
+    Milder(i32),
+}
+
+impl Adaptive {
+    // Stored as a kind and the id of the milder rule.
+    pub fn from_parts(kind: i16, rule_id: i32) -> Self {
+        match kind {
+            1 => Adaptive::Lift,
+            2 => Adaptive::Milder(rule_id),
+            _ => Adaptive::Off,
+        }
+    }
+
+    pub fn to_parts(self) -> (i16, i32) {
+        match self {
+            Adaptive::Off => (0, 0),
+            Adaptive::Lift => (1, 0),
+            Adaptive::Milder(rule_id) => (2, rule_id),
+        }
+    }
+
+    // The rule played instead of the usual one, None when there is nothing to ease.
+    pub fn eased_rule(self) -> Option<i32> {
+        match self {
+            Adaptive::Off => None,
+            Adaptive::Lift => Some(0),
+            Adaptive::Milder(rule_id) => Some(rule_id),
+        }
+    }
+}
+
+// The player with the higher elo, nobody when they are equal.
+pub fn stronger_side(elo_white: i32, elo_black: i32) -> Option<Color> {
+    match elo_white.cmp(&elo_black) {
+        std::cmp::Ordering::Greater => Some(Color::White),
+        std::cmp::Ordering::Less => Some(Color::Black),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+// Compared to the start, so the material given away as odds doesn't count as being behind.
+pub fn is_behind(board: &Board, start: &Board, color: Color) -> bool {
+    let balance = |board: &Board| material(board, color) as i32 - material(board, !color) as i32;
+    balance(board) < balance(start)
+}
+
+// The rule was eased (true) or tightened again (false) in the position after this many moves.
+#[derive(Debug, PartialEq)]
+pub struct HandicapEvent {
+    pub ply: u16,
+    pub eased: bool,
+}
+
+pub fn handicap_events(history: &History, stronger: Color) -> Vec<HandicapEvent> {
+    let positions = history
+        .moves()
+        .map(|(board, _)| *board)
+        .skip(1)
+        .chain([*history.board()]);
+    let mut eased = false;
+    let mut events = Vec::new();
+    for (index, board) in positions.enumerate() {
+        if is_behind(&board, history.start(), stronger) != eased {
+            eased = !eased;
+            events.push(HandicapEvent {
+                ply: index as u16 + 1,
+                eased,
+            });
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{ChessMove, Game, Square};
+
+    #[test]
+    fn parts_round_trip() {
+        for adaptive in [Adaptive::Off, Adaptive::Lift, Adaptive::Milder(12)] {
+            let (kind, rule_id) = adaptive.to_parts();
+            assert_eq!(adaptive, Adaptive::from_parts(kind, rule_id));
+        }
+    }
+
+    #[test]
+    // White loses a pawn on move 2 and wins it back on move 3.
+    fn events_follow_material() {
+        let mut game = Game::new();
+        for (source, dest) in [
+            (Square::E2, Square::E4),
+            (Square::D7, Square::D5),
+            (Square::D2, Square::D3),
+            (Square::D5, Square::E4),
+            (Square::D3, Square::E4),
+        ] {
+            game.make_move(ChessMove::new(source, dest, None));
+        }
+        let history = History::new(Board::default(), &game);
+        assert_eq!(
+            handicap_events(&history, Color::White),
+            vec![
+                HandicapEvent {
+                    ply: 4,
+                    eased: true
+                },
+                HandicapEvent {
+                    ply: 5,
+                    eased: false
+                },
+            ]
+        );
+        assert!(handicap_events(&history, Color::Black).is_empty());
+    }
+}
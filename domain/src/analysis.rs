@@ -0,0 +1,160 @@
+// Answers what a player under one or more rules can do in a position, without a game.
+use crate::filters::context::FilterContext;
+use crate::filters::history::History;
+use crate::gamestate::GameState;
+use crate::move_generator::generate_moves;
+use chess::{BoardStatus, ChessMove, Color, MoveGen};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub enum AnalysisError {
+    InvalidFen(String),
+    IllegalMove(String),
+}
+
+// Why the side to move has no allowed moves left.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Checkmate,
+    Stalemate,
+    // There are legal moves, but the rules forbid all of them.
+    NoAllowedMoves,
+}
+
+impl Outcome {
+    // The same as in a game: stalemate is still a draw, otherwise the side to move loses.
+    pub fn winner(self, side_to_move: Color) -> Option<Color> {
+        match self {
+            Outcome::Stalemate => None,
+            Outcome::Checkmate | Outcome::NoAllowedMoves => Some(!side_to_move),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Analysis {
+    pub side_to_move: Color,
+    pub allowed: Vec<ChessMove>,
+    // Legal moves together with the rules that forbid them.
+    pub forbidden: Vec<(ChessMove, Vec<i32>)>,
+    pub outcome: Option<Outcome>,
+}
+
+// The history is played from the FEN, in the same notation as moves in a game. With more than one
+// rule a move has to be allowed by all of them.
+pub fn analyze(fen: &str, history: &[String], rule_ids: &[i32]) -> Result<Analysis, AnalysisError> {
+    let mut game =
+        GameState::from_str(fen).map_err(|_| AnalysisError::InvalidFen(fen.to_string()))?;
+    for played in history {
+        let chess_move = ChessMove::from_str(played)
+            .map_err(|_| AnalysisError::IllegalMove(played.to_string()))?;
+        if !game.make_move(chess_move) {
+            return Err(AnalysisError::IllegalMove(played.to_string()));
+        }
+    }
+    let context = FilterContext::new(History::with_bonus_moves(&[], &game), None)
+        .starting_at_move(fullmove_number(fen));
+    let allowed_by: Vec<(i32, Vec<ChessMove>)> = rule_ids
+        .iter()
+        .map(|&rule_id| (rule_id, generate_moves(rule_id, &context)))
+        .collect();
+    let mut allowed = Vec::new();
+    let mut forbidden = Vec::new();
+    for chess_move in MoveGen::new_legal(&context.board) {
+        let rules: Vec<i32> = allowed_by
+            .iter()
+            .filter(|(_, moves)| !moves.contains(&chess_move))
+            .map(|(rule_id, _)| *rule_id)
+            .collect();
+        if rules.is_empty() {
+            allowed.push(chess_move);
+        } else {
+            forbidden.push((chess_move, rules));
+        }
+    }
+    let outcome = match context.board.status() {
+        BoardStatus::Checkmate => Some(Outcome::Checkmate),
+        BoardStatus::Stalemate => Some(Outcome::Stalemate),
+        BoardStatus::Ongoing if allowed.is_empty() => Some(Outcome::NoAllowedMoves),
+        BoardStatus::Ongoing => None,
+    };
+    Ok(Analysis {
+        side_to_move: context.side,
+        allowed,
+        forbidden,
+        outcome,
+    })
+}
+
+// The sixth field of the FEN, chess::Board doesn't keep it. Missing means the first move.
+fn fullmove_number(fen: &str) -> u16 {
+    fen.split_whitespace()
+        .nth(5)
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Square;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn forbidden_moves_name_their_rules() {
+        // Stealo 39: can't play on the c file, 40: can't play on the h file.
+        let analysis = analyze(START, &[], &[39, 40]).unwrap();
+        assert_eq!(analysis.allowed.len(), 14);
+        let c4 = ChessMove::new(Square::C2, Square::C4, None);
+        let h3 = ChessMove::new(Square::G1, Square::H3, None);
+        assert!(analysis.forbidden.contains(&(c4, vec![39])));
+        assert!(analysis.forbidden.contains(&(h3, vec![40])));
+        assert_eq!(analysis.outcome, None);
+    }
+
+    #[test]
+    fn fullmove_number_counts_for_turn_based_rules() {
+        // Stealo 24: the queen can't move after move 6.
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 7";
+        let queen_moves = |analysis: &Analysis| {
+            analysis
+                .allowed
+                .iter()
+                .filter(|chess_move| chess_move.get_source() == Square::D1)
+                .count()
+        };
+        assert_eq!(queen_moves(&analyze(fen, &[], &[24]).unwrap()), 0);
+        let fen = fen.replace(" 0 7", " 0 2");
+        assert_eq!(queen_moves(&analyze(&fen, &[], &[24]).unwrap()), 4);
+    }
+
+    #[test]
+    fn history_is_played_first() {
+        let history = ["f2f3", "e7e5", "g2g4", "d8h4"].map(String::from);
+        let analysis = analyze(START, &history, &[0]).unwrap();
+        assert_eq!(analysis.outcome, Some(Outcome::Checkmate));
+        assert_eq!(
+            analysis.outcome.unwrap().winner(analysis.side_to_move),
+            Some(Color::Black)
+        );
+        assert_eq!(
+            analyze(START, &["e2e5".to_string()], &[0]),
+            Err(AnalysisError::IllegalMove("e2e5".to_string()))
+        );
+        assert!(analyze("not a fen", &[], &[0]).is_err());
+    }
+
+    #[test]
+    fn rules_can_forbid_every_move() {
+        // Stealo 57: white has to begin with e2-e4, which is blocked here.
+        let fen = "rnbqkbnr/pppppppp/8/8/8/4P3/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
+        let analysis = analyze(fen, &[], &[57]).unwrap();
+        assert!(analysis.allowed.is_empty());
+        assert_eq!(analysis.outcome, Some(Outcome::NoAllowedMoves));
+        assert_eq!(
+            analysis.outcome.unwrap().winner(Color::White),
+            Some(Color::Black)
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use chess::{get_king_moves, BitBoard, Board, BoardBuilder, CastleRights, Piece, Square, EMPTY};
+use std::fmt;
+use std::str::FromStr;
+
+// Bonus rules give the weaker player a move normal chess doesn't have, instead of taking
+// moves away from the stronger one. Every bonus can be used once per game.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bonus {
+    // A pawn may move two squares forward from any rank.
+    LongPawnStep,
+    // A knight may move like a king.
+    KnightKingStep,
+    // Take back your last move and the opponent's reply.
+    Undo,
+}
+
+impl Bonus {
+    pub fn from_rule(rule_id: i32) -> Option<Self> {
+        match rule_id {
+            82 => Some(Bonus::LongPawnStep),
+            83 => Some(Bonus::KnightKingStep),
+            84 => Some(Bonus::Undo),
+            _ => None,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Bonus::LongPawnStep),
+            1 => Some(Bonus::KnightKingStep),
+            2 => Some(Bonus::Undo),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u8 {
+        match self {
+            Bonus::LongPawnStep => 0,
+            Bonus::KnightKingStep => 1,
+            Bonus::Undo => 2,
+        }
+    }
+}
+
+// Written as "+e3e5", or "+undo" for the undo token, so it can't be mistaken for a normal move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BonusMove {
+    pub bonus: Bonus,
+    pub source: Square,
+    pub dest: Square,
+}
+
+impl BonusMove {
+    pub fn undo() -> Self {
+        Self {
+            bonus: Bonus::Undo,
+            source: Square::A1,
+            dest: Square::A1,
+        }
+    }
+
+    // The bonus isn't part of the notation, the rule of the player decides which one it is.
+    pub fn parse(bonus: Bonus, notation: &str) -> Option<Self> {
+        let squares = notation.strip_prefix('+')?;
+        if bonus == Bonus::Undo {
+            return (squares == "undo").then(BonusMove::undo);
+        }
+        Some(Self {
+            bonus,
+            source: Square::from_str(squares.get(0..2)?).ok()?,
+            dest: Square::from_str(squares.get(2..4)?).ok()?,
+        })
+    }
+}
+
+impl fmt::Display for BonusMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.bonus {
+            Bonus::Undo => write!(f, "+undo"),
+            _ => write!(f, "+{}{}", self.source, self.dest),
+        }
+    }
+}
+
+// The bonus moves of the side to move that change the board. The undo token depends on the
+// moves played before, so ChessGame decides when it can be used.
+pub fn bonus_moves(bonus: Bonus, board: &Board) -> Vec<BonusMove> {
+    let color = board.side_to_move();
+    let candidates: Vec<BonusMove> = match bonus {
+        Bonus::LongPawnStep => (board.pieces(Piece::Pawn) & board.color_combined(color))
+            .filter_map(|source| long_pawn_step(board, source))
+            .collect(),
+        Bonus::KnightKingStep => (board.pieces(Piece::Knight) & board.color_combined(color))
+            .flat_map(|source| {
+                (get_king_moves(source) & !board.color_combined(color)).map(move |dest| BonusMove {
+                    bonus,
+                    source,
+                    dest,
+                })
+            })
+            .collect(),
+        Bonus::Undo => Vec::new(),
+    };
+    candidates
+        .into_iter()
+        .filter(|bonus_move| play(board, bonus_move).is_some())
+        .collect()
+}
+
+// Only from ranks where normal chess doesn't allow it, and never onto the last rank.
+fn long_pawn_step(board: &Board, source: Square) -> Option<BonusMove> {
+    let color = board.side_to_move();
+    if source.get_rank() == color.to_second_rank() {
+        return None;
+    }
+    let over = source.forward(color)?;
+    let dest = over.forward(color)?;
+    let occupied = board.combined();
+    if dest.get_rank() == color.to_their_backrank()
+        || occupied & BitBoard::from_square(over) != EMPTY
+        || occupied & BitBoard::from_square(dest) != EMPTY
+    {
+        return None;
+    }
+    Some(BonusMove {
+        bonus: Bonus::LongPawnStep,
+        source,
+        dest,
+    })
+}
+
+// The position after the bonus move, None if it would leave the own king in check or capture
+// the opposing king.
+pub fn play(board: &Board, bonus_move: &BonusMove) -> Option<Board> {
+    let color = board.side_to_move();
+    let piece = board.piece_on(bonus_move.source)?;
+    if board.color_on(bonus_move.source) != Some(color)
+        || board.piece_on(bonus_move.dest) == Some(Piece::King)
+        || bonus_move.bonus == Bonus::Undo
+    {
+        return None;
+    }
+    let mut builder = BoardBuilder::from(board);
+    let lost_rights = CastleRights::square_to_castle_rights(!color, bonus_move.dest);
+    let opponent_rights = builder.get_castle_rights(!color).remove(lost_rights);
+    builder
+        .clear_square(bonus_move.source)
+        .piece(bonus_move.dest, piece, color)
+        .side_to_move(!color)
+        .en_passant(None)
+        .castle_rights(!color, opponent_rights);
+    Board::try_from(&builder).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Color;
+
+    #[test]
+    fn pawn_steps_twice_from_any_rank() {
+        let board = Board::from_str("4k3/8/8/8/8/4P3/P7/4K3 w - - 0 1").unwrap();
+        let moves = bonus_moves(Bonus::LongPawnStep, &board);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].to_string(), "+e3e5");
+        let after = play(&board, &moves[0]).unwrap();
+        assert_eq!(after.piece_on(Square::E5), Some(Piece::Pawn));
+        assert_eq!(after.side_to_move(), Color::Black);
+    }
+
+    #[test]
+    fn pawn_cant_step_onto_last_rank() {
+        let board = Board::from_str("k7/8/4P3/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(bonus_moves(Bonus::LongPawnStep, &board).is_empty());
+    }
+
+    #[test]
+    fn knight_moves_like_a_king() {
+        let board = Board::from_str("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+        let moves: Vec<String> = bonus_moves(Bonus::KnightKingStep, &board)
+            .iter()
+            .map(|m| m.to_string())
+            .collect();
+        assert_eq!(moves.len(), 3);
+        assert!(moves.contains(&"+a1b2".to_string()));
+    }
+
+    #[test]
+    fn bonus_move_cant_expose_the_king() {
+        // The knight on e2 is pinned by the rook on e8, it can only step along the e-file.
+        let board = Board::from_str("4r2k/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+        let moves = bonus_moves(Bonus::KnightKingStep, &board);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].to_string(), "+e2e3");
+    }
+
+    #[test]
+    fn capturing_a_rook_removes_castling() {
+        let board = Board::from_str("4k2r/7N/8/8/8/8/8/4K3 w k - 0 1").unwrap();
+        let bonus_move = BonusMove::parse(Bonus::KnightKingStep, "+h7h8").unwrap();
+        let after = play(&board, &bonus_move).unwrap();
+        assert_eq!(after.castle_rights(Color::Black), CastleRights::NoRights);
+    }
+
+    #[test]
+    fn notation_round_trips() {
+        let bonus_move = BonusMove::parse(Bonus::LongPawnStep, "+e3e5").unwrap();
+        assert_eq!(bonus_move.to_string(), "+e3e5");
+        assert_eq!(
+            BonusMove::parse(Bonus::Undo, "+undo"),
+            Some(BonusMove::undo())
+        );
+        assert_eq!(BonusMove::parse(Bonus::LongPawnStep, "e3e5"), None);
+    }
+}
@@ -1,23 +1,64 @@
-use crate::move_generator::generate_moves;
+use crate::adaptive::{handicap_events, is_behind, stronger_side, Adaptive, HandicapEvent};
+use crate::bonus::{bonus_moves, play, Bonus, BonusMove};
+use crate::clock::{now_ms, Clock};
+use crate::commitment::commit;
+use crate::filters::context::FilterContext;
+use crate::filters::history::History;
+pub use crate::filters::obligation::Progress;
+use crate::gamestate::GameState;
+use crate::guessing::{guess_points, GuessError, GuessReward, DEFAULT_MAX_GUESSES};
+use crate::move_generator::{
+    generate_moves, obligation_progress, remaining_budget, HIGHEST_FILTER_ID,
+};
+use crate::movecache::PositionCache;
+use crate::odds::{MaterialOdds, TimeOdds};
+use crate::opponent::Opponent;
 use crate::stringtomove::string_to_move;
+use crate::visibility::RuleVisibility;
 use chess::GameResult::{BlackResigns, Stalemate, WhiteResigns};
-use chess::{Action, Board, ChessMove, Color, Game, MoveGen};
+use chess::{Action, Board, ChessMove, Color, MoveGen};
 
 pub struct ChessGame {
     pub white: String,
     pub black: String,
-    pub game: Game,
+    pub game: GameState,
     pub elo_white: i32,
     pub elo_black: i32,
     pub rule_id_white: i32,
     pub rule_id_black: i32,
+    pub visibility: RuleVisibility,
+    pub forbidden_attempt_white: bool,
+    pub forbidden_attempt_black: bool,
+    pub max_guesses: u8,
+    pub guess_reward: GuessReward,
+    // Rule ids guessed by white and black respectively.
+    pub guesses_white: Vec<i32>,
+    pub guesses_black: Vec<i32>,
+    // Secret half of the rule commitment, empty for games without one.
+    pub nonce: String,
+    // Made once from the rules and nonce at the start, see commit_rules. It is stored and never
+    // recomputed, so it still holds whatever happens to the rules later.
+    pub commitment: String,
+    pub adaptive: Adaptive,
+    // The game before each bonus move. A GameState can't hold a bonus move, so the game
+    // continues in a new one from the position after it.
+    pub earlier_games: Vec<(GameState, BonusMove)>,
+    pub bonus_used_white: bool,
+    pub bonus_used_black: bool,
+    pub material_odds: MaterialOdds,
+    // The player giving the material and time odds.
+    pub odds_giver: Color,
+    // Only games with time odds have a clock.
+    pub clock: Option<Clock>,
+    pub opponent: Opponent,
+    // The allowed moves of the current position, see get_moves.
+    pub moves_cache: PositionCache,
 }
 
 impl ChessGame {
     pub fn get_move_gen(&self) -> MoveGen {
         let board = self.game.current_position();
-        let moves = MoveGen::new_legal(&board);
-        moves
+        MoveGen::new_legal(&board)
     }
 
     pub fn get_position(&self) -> Board {
@@ -30,24 +71,240 @@ impl ChessGame {
             Some("black") => Some(Color::Black),
             _ => None,
         };
-        if move_to_make == "resign" && side_to_move.is_some() {
-            self.game.resign(side_to_move.unwrap());
-        } else {
-            let chess_move = string_to_move(move_to_make);
-            if self.get_moves().contains(&chess_move) {
-                self.game.make_move(chess_move);
+        match (move_to_make.as_str(), side_to_move) {
+            ("resign", Some(color)) => {
+                self.game.resign(color);
+                self.moves_cache.clear();
+            }
+            (bonus_move, _) if bonus_move.starts_with('+') => {
+                self.play_bonus_move(bonus_move);
+            }
+            _ => {
+                let Ok(chess_move) = string_to_move(&move_to_make) else {
+                    return;
+                };
+                if self.get_moves().contains(&chess_move) {
+                    let mover = self.game.side_to_move();
+                    self.game.make_move(chess_move);
+                    self.moves_cache.clear();
+                    self.press_clock(mover);
+                } else if self.game.result().is_none()
+                    && self.get_move_gen().any(|m| m == chess_move)
+                {
+                    // Legal chess, but the player's own rule forbids it.
+                    match self.game.side_to_move() {
+                        Color::White => self.forbidden_attempt_white = true,
+                        Color::Black => self.forbidden_attempt_black = true,
+                    }
+                }
             }
         }
     }
 
+    // Computed once per action, the clock is checked every time.
     pub fn get_moves(&self) -> Vec<ChessMove> {
-        if self.game.result().is_some() {
-            Vec::new()
-        } else if self.game.side_to_move() == Color::White {
-            generate_moves(self.rule_id_white, &self.game)
+        if self.game.result().is_some() || self.flagged() {
+            return Vec::new();
+        }
+        let rule_id = self.active_rule(self.game.side_to_move());
+        let actions = self.earlier_games.len()
+            + self
+                .earlier_games
+                .iter()
+                .map(|(game, _)| game.actions().len())
+                .sum::<usize>()
+            + self.game.actions().len();
+        let key = (rule_id, actions, self.get_position().get_hash());
+        self.moves_cache
+            .get_or_insert(key, || generate_moves(rule_id, &self.filter_context()))
+    }
+
+    // Moves the side to move may play on top of get_moves, if its rule is a bonus rule.
+    pub fn get_bonus_moves(&self) -> Vec<BonusMove> {
+        let color = self.game.side_to_move();
+        let bonus = match Bonus::from_rule(self.active_rule(color)) {
+            Some(bonus) if !self.bonus_used(color) && !self.get_moves().is_empty() => bonus,
+            _ => return Vec::new(),
+        };
+        match bonus {
+            Bonus::Undo if self.can_undo() => vec![BonusMove::undo()],
+            Bonus::Undo => Vec::new(),
+            _ => bonus_moves(bonus, &self.get_position()),
+        }
+    }
+
+    pub fn get_bonus_moves_string(&self) -> Vec<String> {
+        let moves = self.get_bonus_moves();
+        moves.iter().map(|m| m.to_string()).collect()
+    }
+
+    pub fn bonus_used(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.bonus_used_white,
+            Color::Black => self.bonus_used_black,
+        }
+    }
+
+    fn play_bonus_move(&mut self, notation: &str) {
+        let color = self.game.side_to_move();
+        let bonus_move = match Bonus::from_rule(self.active_rule(color)) {
+            Some(bonus) => BonusMove::parse(bonus, notation),
+            None => None,
+        };
+        let bonus_move = match bonus_move {
+            Some(bonus_move) if self.get_bonus_moves().contains(&bonus_move) => bonus_move,
+            _ => return,
+        };
+        if bonus_move.bonus == Bonus::Undo {
+            // Replay everything but the own last move and the opponent's reply.
+            let actions = self.game.actions();
+            let mut game = GameState::new_with_board(self.game.start_position());
+            for action in &actions[..actions.len() - 2] {
+                replay(&mut game, action);
+            }
+            self.game = game;
         } else {
-            generate_moves(self.rule_id_black, &self.game)
+            let board = play(&self.get_position(), &bonus_move).expect("bonus move is legal");
+            let earlier = std::mem::replace(&mut self.game, GameState::new_with_board(board));
+            self.earlier_games.push((earlier, bonus_move));
+        }
+        match color {
+            Color::White => self.bonus_used_white = true,
+            Color::Black => self.bonus_used_black = true,
         }
+        self.moves_cache.clear();
+        self.press_clock(color);
+    }
+
+    // Has to be done before the first move, the game starts over from the odds position.
+    pub fn give_odds(&mut self, material: MaterialOdds, time: Option<TimeOdds>, giver: Color) {
+        self.material_odds = material;
+        self.odds_giver = giver;
+        self.clock = time.map(|time| time.clock(giver));
+        self.game = GameState::new_with_board(material.start_position(giver));
+        self.moves_cache.clear();
+    }
+
+    fn press_clock(&mut self, mover: Color) {
+        if let Some(clock) = self.clock.as_mut() {
+            clock.press(mover, now_ms());
+        }
+    }
+
+    // Whether the side to move has run out of time, which loses like having no moves left.
+    pub fn flagged(&self) -> bool {
+        self.clock
+            .is_some_and(|clock| clock.flagged(self.game.side_to_move(), now_ms()))
+    }
+
+    // Time left for this color right now, None for games without a clock.
+    pub fn time_left(&self, color: Color) -> Option<u64> {
+        self.clock
+            .map(|clock| clock.remaining(color, self.game.side_to_move(), now_ms()))
+    }
+
+    // There has to be an own move and a reply to take back since the last bonus move.
+    fn can_undo(&self) -> bool {
+        let actions = self.game.actions();
+        actions.len() >= 2
+            && actions[actions.len() - 2..]
+                .iter()
+                .all(|action| matches!(action, Action::MakeMove(_)))
+    }
+
+    // Every move since the start of the game, the bonus moves in between included.
+    pub fn history(&self) -> History {
+        History::with_bonus_moves(&self.earlier_games, &self.game)
+    }
+
+    fn filter_context(&self) -> FilterContext {
+        FilterContext::new(self.history(), self.clock)
+    }
+
+    // The rule currently restricting this color, which is normal chess once it has been lifted.
+    pub fn active_rule(&self, color: Color) -> i32 {
+        let lifted = self.guess_reward == GuessReward::RevealAndLift && self.rule_guessed(color);
+        match (lifted, self.eased_rule(color), color) {
+            (true, _, _) => 0,
+            (false, Some(rule_id), _) => rule_id,
+            (false, None, Color::White) => self.rule_id_white,
+            (false, None, Color::Black) => self.rule_id_black,
+        }
+    }
+
+    // The adaptive handicap's rule, while this color is the stronger side and behind in material.
+    pub fn eased_rule(&self, color: Color) -> Option<i32> {
+        let stronger = stronger_side(self.elo_white, self.elo_black)?;
+        let start = self.material_odds.start_position(self.odds_giver);
+        if stronger != color || !is_behind(&self.get_position(), &start, color) {
+            return None;
+        }
+        self.adaptive.eased_rule()
+    }
+
+    // When the adaptive handicap switched during the game.
+    pub fn handicap_events(&self) -> Vec<HandicapEvent> {
+        match (self.adaptive, stronger_side(self.elo_white, self.elo_black)) {
+            (Adaptive::Off, _) | (_, None) => Vec::new(),
+            (_, Some(stronger)) => handicap_events(&self.history(), stronger),
+        }
+    }
+
+    // Only for rules that limit how often something can be done.
+    pub fn remaining_budget(&self, color: Color) -> Option<u16> {
+        remaining_budget(self.active_rule(color), &self.filter_context(), color)
+    }
+
+    // Only for rules with goals that have to be reached by a deadline.
+    pub fn obligation_progress(&self, color: Color) -> Option<Progress> {
+        obligation_progress(self.active_rule(color), &self.filter_context(), color)
+    }
+
+    pub fn guesses(&self, guesser: Color) -> &Vec<i32> {
+        match guesser {
+            Color::White => &self.guesses_white,
+            Color::Black => &self.guesses_black,
+        }
+    }
+
+    pub fn guesses_left(&self, guesser: Color) -> u8 {
+        self.max_guesses
+            .saturating_sub(self.guesses(guesser).len() as u8)
+    }
+
+    // Whether the opponent of this color has correctly guessed its rule.
+    pub fn rule_guessed(&self, color: Color) -> bool {
+        let rule_id = match color {
+            Color::White => self.rule_id_white,
+            Color::Black => self.rule_id_black,
+        };
+        self.guesses(!color).contains(&rule_id)
+    }
+
+    pub fn guess_points(&self, guesser: Color) -> u32 {
+        let rule_id = match guesser {
+            Color::White => self.rule_id_black,
+            Color::Black => self.rule_id_white,
+        };
+        guess_points(self.guesses(guesser), rule_id, self.max_guesses)
+    }
+
+    // Returns whether the guess was correct.
+    pub fn guess_rule(&mut self, guesser: Color, rule_id: i32) -> Result<bool, GuessError> {
+        if self.get_moves().is_empty() {
+            return Err(GuessError::GameOver);
+        }
+        if self.rule_revealed(!guesser) || self.rule_guessed(!guesser) {
+            return Err(GuessError::AlreadyRevealed);
+        }
+        if self.guesses_left(guesser) == 0 {
+            return Err(GuessError::NoGuessesLeft);
+        }
+        match guesser {
+            Color::White => self.guesses_white.push(rule_id),
+            Color::Black => self.guesses_black.push(rule_id),
+        }
+        Ok(self.rule_guessed(!guesser))
     }
 
     pub fn get_moves_string(&self) -> Vec<String> {
@@ -55,15 +312,53 @@ impl ChessGame {
         moves.iter().map(|m| m.to_string()).collect()
     }
 
+    // Bonus moves count as moves too.
     pub fn turn(&self) -> u16 {
-        let mut counter: u16 = 2;
-        for action in self.game.actions() {
-            match action {
-                Action::MakeMove(_) => counter += 1,
-                _ => {}
-            }
+        let games = self.earlier_games.iter().map(|(game, _)| game);
+        let moves: usize = games
+            .chain([&self.game])
+            .map(|game| game.moves().len())
+            .sum();
+        (2 + self.earlier_games.len() + moves) as u16 / 2
+    }
+
+    // Whether the rule of this color may be shown to the opponent (and spectators).
+    // Every rule is revealed when the game has ended.
+    pub fn rule_revealed(&self, color: Color) -> bool {
+        let revealed_by_policy = match self.visibility {
+            RuleVisibility::Open => true,
+            RuleVisibility::Hidden => false,
+            RuleVisibility::RevealAfter(moves) => self.turn() > moves,
+            RuleVisibility::RevealOnForbiddenAttempt => match color {
+                Color::White => self.forbidden_attempt_white,
+                Color::Black => self.forbidden_attempt_black,
+            },
+        };
+        let revealed_by_guess =
+            self.guess_reward == GuessReward::RevealAndLift && self.rule_guessed(color);
+        revealed_by_policy || revealed_by_guess || self.get_moves().is_empty()
+    }
+
+    // Fixes the rules of a new game, see commitment::commit.
+    pub fn commit_rules(&mut self, nonce: String) {
+        self.commitment = commit(self.rule_id_white, self.rule_id_black, &nonce);
+        self.nonce = nonce;
+    }
+
+    // Published at the start of the game.
+    pub fn commitment(&self) -> Option<String> {
+        if self.commitment.is_empty() {
+            return None;
         }
-        counter / 2
+        Some(self.commitment.clone())
+    }
+
+    // The nonce may only be shown when the game has ended.
+    pub fn revealed_nonce(&self) -> Option<&str> {
+        if self.nonce.is_empty() || !self.get_moves().is_empty() {
+            return None;
+        }
+        Some(&self.nonce)
     }
 
     // Only called when there are no available moves after filtering.
@@ -80,6 +375,31 @@ impl ChessGame {
     }
 }
 
+fn replay(game: &mut GameState, action: &Action) {
+    match *action {
+        Action::MakeMove(chess_move) => {
+            game.make_move(chess_move);
+        }
+        Action::OfferDraw(color) => {
+            game.offer_draw(color);
+        }
+        Action::AcceptDraw => {
+            game.accept_draw();
+        }
+        Action::DeclareDraw => {
+            game.declare_draw();
+        }
+        Action::Resign(color) => {
+            game.resign(color);
+        }
+    }
+}
+
+// Rule 0 is normal chess.
+pub fn is_known_rule(rule_id: i32) -> bool {
+    (0..=HIGHEST_FILTER_ID).contains(&rule_id)
+}
+
 pub fn new_game(
     player1: String,
     player2: String,
@@ -88,7 +408,7 @@ pub fn new_game(
     stealo1: i32,
     stealo2: i32,
 ) -> ChessGame {
-    let g = Game::new();
+    let g = GameState::new();
     ChessGame {
         white: player1,
         black: player2,
@@ -97,12 +417,31 @@ pub fn new_game(
         elo_black: elo2,
         rule_id_white: stealo1,
         rule_id_black: stealo2,
+        visibility: RuleVisibility::default(),
+        forbidden_attempt_white: false,
+        forbidden_attempt_black: false,
+        max_guesses: DEFAULT_MAX_GUESSES,
+        guess_reward: GuessReward::default(),
+        guesses_white: Vec::new(),
+        guesses_black: Vec::new(),
+        nonce: String::new(),
+        commitment: String::new(),
+        adaptive: Adaptive::default(),
+        earlier_games: Vec::new(),
+        bonus_used_white: false,
+        bonus_used_black: false,
+        material_odds: MaterialOdds::default(),
+        odds_giver: Color::White,
+        clock: None,
+        opponent: Opponent::default(),
+        moves_cache: PositionCache::default(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chess::{Piece, Square};
     use std::str::FromStr;
 
     #[test]
@@ -126,7 +465,7 @@ mod tests {
     fn stalemate_test() {
         let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 0, 0);
         let stalemate_position = Board::from_str("k7/8/8/8/8/8/2q5/K7 w - - 0 1").unwrap();
-        game.game = Game::new_with_board(stalemate_position);
+        game.game = GameState::new_with_board(stalemate_position);
         assert_eq!("draw".to_string(), game.winner_when_no_moves());
     }
 
@@ -150,6 +489,240 @@ mod tests {
         assert_eq!(game.get_moves().len(), 0);
     }
 
+    #[test]
+    fn rules_hidden_until_game_ends() {
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 5, 6);
+        assert!(!game.rule_revealed(Color::White));
+        game.make_move("resign".to_string(), Some("black".to_string()));
+        assert!(game.rule_revealed(Color::White));
+        assert!(game.rule_revealed(Color::Black));
+    }
+
+    #[test]
+    fn rules_revealed_after_moves() {
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 5, 6);
+        game.visibility = RuleVisibility::RevealAfter(1);
+        game.make_move("e2e4".to_string(), None);
+        assert!(!game.rule_revealed(Color::Black));
+        game.make_move("e7e5".to_string(), None);
+        assert!(game.rule_revealed(Color::White));
+        assert!(game.rule_revealed(Color::Black));
+    }
+
+    #[test]
+    fn rule_revealed_on_forbidden_attempt() {
+        // Stealo 59: white has to begin with Nb1-a3
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 59, 0);
+        game.visibility = RuleVisibility::RevealOnForbiddenAttempt;
+        game.make_move("e2e5".to_string(), None);
+        assert!(!game.rule_revealed(Color::White));
+        game.make_move("e2e4".to_string(), None);
+        assert!(game.rule_revealed(Color::White));
+        assert!(!game.rule_revealed(Color::Black));
+    }
+
+    #[test]
+    fn correct_guess_scores_points() {
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 5, 6);
+        assert_eq!(game.guess_rule(Color::White, 7), Ok(false));
+        assert_eq!(game.guess_rule(Color::White, 6), Ok(true));
+        assert_eq!(game.guess_points(Color::White), 2);
+        assert_eq!(game.guess_points(Color::Black), 0);
+        assert!(!game.rule_revealed(Color::Black));
+    }
+
+    #[test]
+    fn guesses_are_limited() {
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 5, 6);
+        game.max_guesses = 1;
+        assert_eq!(game.guess_rule(Color::Black, 7), Ok(false));
+        assert_eq!(
+            game.guess_rule(Color::Black, 5),
+            Err(GuessError::NoGuessesLeft)
+        );
+        assert_eq!(game.guesses_left(Color::White), 1);
+    }
+
+    #[test]
+    fn correct_guess_lifts_rule() {
+        // Stealo 59: white has to begin with Nb1-a3
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 59, 0);
+        game.guess_reward = GuessReward::RevealAndLift;
+        assert_eq!(game.get_moves().len(), 1);
+        assert_eq!(game.guess_rule(Color::Black, 59), Ok(true));
+        assert!(game.rule_revealed(Color::White));
+        assert_eq!(game.get_moves().len(), 20);
+        assert_eq!(
+            game.guess_rule(Color::Black, 59),
+            Err(GuessError::AlreadyRevealed)
+        );
+    }
+
+    #[test]
+    fn nonce_revealed_at_game_end() {
+        let mut game = new_game(
+            "AtoomBlom".to_string(),
+            "Opponent".to_string(),
+            0,
+            0,
+            12,
+            37,
+        );
+        game.commit_rules("nonce".to_string());
+        let commitment = game.commitment().unwrap();
+        assert_eq!(game.revealed_nonce(), None);
+        game.make_move("resign".to_string(), Some("white".to_string()));
+        let nonce = game.revealed_nonce().unwrap();
+        assert!(crate::commitment::verify(&commitment, 12, 37, nonce));
+    }
+
+    #[test]
+    fn malformed_move_is_ignored() {
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 0, 0);
+        game.make_move("zz99".to_string(), None);
+        game.make_move("resign".to_string(), None);
+        assert_eq!(game.get_position(), Board::default());
+        assert!(game.game.result().is_none());
+    }
+
+    #[test]
+    fn commitment_is_not_recomputed() {
+        let mut game = new_game(
+            "AtoomBlom".to_string(),
+            "Opponent".to_string(),
+            0,
+            0,
+            12,
+            37,
+        );
+        game.commit_rules("nonce".to_string());
+        let commitment = game.commitment().unwrap();
+        // Swapped rules no longer match what was published at the start.
+        game.rule_id_white = 37;
+        game.rule_id_black = 12;
+        assert_eq!(game.commitment().unwrap(), commitment);
+        assert!(!crate::commitment::verify(
+            &commitment,
+            game.rule_id_white,
+            game.rule_id_black,
+            &game.nonce
+        ));
+    }
+
+    #[test]
+    fn budget_is_spent() {
+        // Stealo 67: only 5 captures all game
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 67, 0);
+        game.make_move("e2e4".to_string(), None);
+        game.make_move("d7d5".to_string(), None);
+        game.make_move("e4d5".to_string(), None);
+        assert_eq!(game.remaining_budget(Color::White), Some(4));
+        assert_eq!(game.remaining_budget(Color::Black), None);
+    }
+
+    #[test]
+    fn rule_lifted_while_behind() {
+        // Stealo 5: queen can't capture anything
+        let mut game = new_game(
+            "AtoomBlom".to_string(),
+            "Opponent".to_string(),
+            1800,
+            1200,
+            5,
+            0,
+        );
+        game.adaptive = Adaptive::Lift;
+        game.game = GameState::from_str("3qk3/8/8/8/8/8/2p5/3QK3 w - - 0 1").unwrap();
+        assert_eq!(game.active_rule(Color::White), 0);
+        assert!(game.get_moves_string().contains(&"d1c2".to_string()));
+        game.game = GameState::from_str("4k3/8/8/8/8/8/2p5/3QK3 w - - 0 1").unwrap();
+        assert_eq!(game.active_rule(Color::White), 5);
+        assert!(!game.get_moves_string().contains(&"d1c2".to_string()));
+    }
+
+    #[test]
+    fn bonus_move_is_played_once() {
+        // Stealo 82: once per game, a pawn may move two squares forward from any rank
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 82, 0);
+        game.make_move("e2e3".to_string(), None);
+        game.make_move("e7e6".to_string(), None);
+        assert!(game.get_bonus_moves_string().contains(&"+e3e5".to_string()));
+        game.make_move("+e3e5".to_string(), None);
+        assert_eq!(game.get_position().piece_on(Square::E5), Some(Piece::Pawn));
+        assert_eq!(game.turn(), 2);
+        game.make_move("d7d6".to_string(), None);
+        assert!(game.bonus_used(Color::White));
+        assert!(game.get_bonus_moves().is_empty());
+        assert_eq!(
+            game.get_moves().len(),
+            MoveGen::new_legal(&game.get_position()).len()
+        );
+    }
+
+    #[test]
+    fn undo_takes_back_a_move_and_reply() {
+        // Stealo 84: once per game, take back your last move and the opponent's reply
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 84, 0);
+        assert!(game.get_bonus_moves().is_empty());
+        game.make_move("f2f3".to_string(), None);
+        game.make_move("e7e5".to_string(), None);
+        game.make_move("+undo".to_string(), None);
+        assert_eq!(game.get_position(), Board::default());
+        assert!(game.bonus_used(Color::White));
+        game.make_move("e2e4".to_string(), None);
+        game.make_move("e7e5".to_string(), None);
+        game.make_move("+undo".to_string(), None);
+        assert_eq!(game.game.actions().len(), 2);
+    }
+
+    #[test]
+    fn game_starts_with_material_odds() {
+        // Stealo 67: only 5 captures all game, counted from the odds position
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 67, 0);
+        game.give_odds(MaterialOdds::Knight, None, Color::White);
+        assert_eq!(game.get_position().piece_on(Square::B1), None);
+        game.make_move("e2e4".to_string(), None);
+        game.make_move("d7d5".to_string(), None);
+        game.make_move("e4d5".to_string(), None);
+        assert_eq!(game.remaining_budget(Color::White), Some(4));
+    }
+
+    #[test]
+    fn odds_dont_count_as_behind() {
+        // Stealo 5: queen can't capture anything
+        let mut game = new_game(
+            "AtoomBlom".to_string(),
+            "Opponent".to_string(),
+            1800,
+            1200,
+            5,
+            0,
+        );
+        game.adaptive = Adaptive::Lift;
+        game.give_odds(MaterialOdds::Knight, None, Color::White);
+        assert_eq!(game.active_rule(Color::White), 5);
+        game.make_move("e2e4".to_string(), None);
+        game.make_move("d7d5".to_string(), None);
+        game.make_move("d2d3".to_string(), None);
+        assert_eq!(game.active_rule(Color::White), 5);
+        assert!(game.handicap_events().is_empty());
+        // A pawn down on top of the knight given away.
+        game.make_move("d5e4".to_string(), None);
+        assert_eq!(game.active_rule(Color::White), 0);
+        assert_eq!(game.handicap_events().len(), 1);
+    }
+
+    #[test]
+    fn flag_fall_loses_the_game() {
+        let mut game = new_game("AtoomBlom".to_string(), "Opponent".to_string(), 0, 0, 0, 0);
+        game.give_odds(MaterialOdds::None, Some(TimeOdds::new(5, 3)), Color::Black);
+        game.make_move("e2e4".to_string(), None);
+        assert_eq!(game.time_left(Color::White), Some(300_000));
+        game.clock.as_mut().unwrap().running_since = Some(now_ms() - 180_000);
+        assert!(game.get_moves().is_empty());
+        assert_eq!(game.winner_when_no_moves(), "white".to_string());
+    }
+
     #[test]
     fn illegal_move_due_to_stealo() {
         // Stealo 59: white has to begin with Nb1-a3
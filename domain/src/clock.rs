@@ -0,0 +1,78 @@
+use chess::Color;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Time left for both players in milliseconds. The clock of the side to move runs from the
+// moment the previous move was made, so it only starts after the first move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clock {
+    pub white_ms: u64,
+    pub black_ms: u64,
+    // Unix time in milliseconds of the last move, None before the first one.
+    pub running_since: Option<u64>,
+}
+
+impl Clock {
+    pub fn new(white_ms: u64, black_ms: u64) -> Self {
+        Self {
+            white_ms,
+            black_ms,
+            running_since: None,
+        }
+    }
+
+    pub fn remaining(&self, color: Color, to_move: Color, now: u64) -> u64 {
+        let stored = match color {
+            Color::White => self.white_ms,
+            Color::Black => self.black_ms,
+        };
+        match self.running_since {
+            Some(since) if color == to_move => stored.saturating_sub(now.saturating_sub(since)),
+            _ => stored,
+        }
+    }
+
+    pub fn flagged(&self, to_move: Color, now: u64) -> bool {
+        self.remaining(to_move, to_move, now) == 0
+    }
+
+    // Stops the clock of the player who just moved and starts the opponent's.
+    pub fn press(&mut self, mover: Color, now: u64) {
+        let remaining = self.remaining(mover, mover, now);
+        match mover {
+            Color::White => self.white_ms = remaining,
+            Color::Black => self.black_ms = remaining,
+        }
+        self.running_since = Some(now);
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_starts_after_the_first_move() {
+        let mut clock = Clock::new(60_000, 60_000);
+        assert_eq!(clock.remaining(Color::White, Color::White, 5_000), 60_000);
+        clock.press(Color::White, 5_000);
+        assert_eq!(clock.white_ms, 60_000);
+        assert_eq!(clock.remaining(Color::Black, Color::Black, 15_000), 50_000);
+        clock.press(Color::Black, 15_000);
+        assert_eq!(clock.black_ms, 50_000);
+    }
+
+    #[test]
+    fn flag_falls_when_time_runs_out() {
+        let mut clock = Clock::new(60_000, 1_000);
+        clock.press(Color::White, 0);
+        assert!(!clock.flagged(Color::Black, 999));
+        assert!(clock.flagged(Color::Black, 1_000));
+    }
+}
@@ -0,0 +1,35 @@
+use sha2::{Digest, Sha256};
+
+// Commit-reveal proof that the hidden rules were fixed before the first move.
+// At the start of the game everyone gets sha256("<white rule>:<black rule>:<nonce>"),
+// the nonce is only published once the game has ended. The nonce has to be long and random,
+// otherwise the small number of rules would make it easy to find the rules from the commitment.
+pub fn commit(rule_id_white: i32, rule_id_black: i32, nonce: &str) -> String {
+    let digest = Sha256::digest(format!("{rule_id_white}:{rule_id_black}:{nonce}"));
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn verify(commitment: &str, rule_id_white: i32, rule_id_black: i32, nonce: &str) -> bool {
+    commit(rule_id_white, rule_id_black, nonce) == commitment.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commitment_is_sha256_of_rules_and_nonce() {
+        assert_eq!(
+            commit(12, 37, "nonce"),
+            "b7077ed50f8ac00cc61062d5e5d55ae68cff1d29a242423d830baa6d7c18a2d5"
+        );
+    }
+
+    #[test]
+    fn verify_rejects_other_rules() {
+        let commitment = commit(12, 37, "nonce");
+        assert!(verify(&commitment, 12, 37, "nonce"));
+        assert!(!verify(&commitment, 37, 12, "nonce"));
+        assert!(!verify(&commitment, 12, 37, "other nonce"));
+    }
+}
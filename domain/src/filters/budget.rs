@@ -0,0 +1,134 @@
+use crate::filters::context::FilterContext;
+use crate::filters::history::{is_capture, History};
+use crate::filters::movesetfilter::MoveSetFilter;
+use chess::{Board, ChessMove, Color, Piece};
+
+// A limited number of moves of a certain kind for the whole game.
+pub struct Budget {
+    counts: fn(&Board, &ChessMove) -> bool,
+    limit: u16,
+}
+
+impl Budget {
+    pub fn five_captures() -> Self {
+        Self {
+            counts: is_capture,
+            limit: 5,
+        }
+    }
+
+    pub fn eight_queen_moves() -> Self {
+        Self {
+            counts: |board, chess_move| {
+                board.piece_on(chess_move.get_source()) == Some(Piece::Queen)
+            },
+            limit: 8,
+        }
+    }
+}
+
+impl Budget {
+    pub fn remaining(&self, history: &History, color: Color) -> u16 {
+        self.limit.saturating_sub(history.count(color, self.counts))
+    }
+}
+
+// The budget is the same for every move, so it is only counted once.
+impl MoveSetFilter for Budget {
+    fn filter_set(&self, context: &FilterContext, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+        if self.remaining(&context.history, context.side) > 0 {
+            return moves;
+        }
+        moves
+            .into_iter()
+            .filter(|chess_move| !(self.counts)(&context.board, chess_move))
+            .collect()
+    }
+}
+
+// The piece that moved last is the one standing on the square it moved to.
+// After castling that is the king.
+#[derive(Default)]
+pub struct NotTwiceInARow {}
+
+impl NotTwiceInARow {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl MoveSetFilter for NotTwiceInARow {
+    fn filter_set(&self, context: &FilterContext, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+        let Some(last_move) = context.history.last_move(context.side) else {
+            return moves;
+        };
+        moves
+            .into_iter()
+            .filter(|chess_move| last_move.get_dest() != chess_move.get_source())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Game, Square};
+
+    fn play(moves: &[(Square, Square)]) -> Game {
+        let mut game = Game::new();
+        for (source, dest) in moves {
+            game.make_move(ChessMove::new(*source, *dest, None));
+        }
+        game
+    }
+
+    #[test]
+    fn queen_runs_out_of_moves() {
+        let mut moves = vec![(Square::E2, Square::E4), (Square::E7, Square::E5)];
+        for _ in 0..4 {
+            moves.push((Square::D1, Square::E2));
+            moves.push((Square::D8, Square::E7));
+            moves.push((Square::E2, Square::D1));
+            moves.push((Square::E7, Square::D8));
+        }
+        let game = play(&moves);
+        let budget = Budget::eight_queen_moves();
+        let history = History::new(Board::default(), &game);
+        assert_eq!(budget.remaining(&history, Color::White), 0);
+        assert!(budget
+            .filter_moves(&FilterContext::from_game(&game))
+            .iter()
+            .all(|m| m.get_source() != Square::D1));
+    }
+
+    #[test]
+    fn captures_are_counted() {
+        let budget = Budget::five_captures();
+        let mut moves = vec![(Square::E2, Square::E4), (Square::D7, Square::D5)];
+        let remaining = |moves: &[(Square, Square)], color| {
+            budget.remaining(&History::new(Board::default(), &play(moves)), color)
+        };
+        assert_eq!(remaining(&moves, Color::White), 5);
+        // 2.exd5 Qxd5 3.Nc3 Qxa2 4.Rxa2, two captures each.
+        moves.push((Square::E4, Square::D5));
+        assert_eq!(remaining(&moves, Color::White), 4);
+        assert_eq!(remaining(&moves, Color::Black), 5);
+        moves.extend([
+            (Square::D8, Square::D5),
+            (Square::B1, Square::C3),
+            (Square::D5, Square::A2),
+        ]);
+        assert_eq!(remaining(&moves, Color::White), 4);
+        assert_eq!(remaining(&moves, Color::Black), 3);
+        moves.push((Square::A1, Square::A2));
+        assert_eq!(remaining(&moves, Color::White), 3);
+    }
+
+    #[test]
+    fn same_piece_cant_move_twice() {
+        let game = play(&[(Square::G1, Square::F3), (Square::E7, Square::E5)]);
+        let moves = NotTwiceInARow::new().filter_moves(&FilterContext::from_game(&game));
+        assert!(moves.iter().all(|m| m.get_source() != Square::F3));
+        assert!(moves.contains(&ChessMove::new(Square::E2, Square::E4, None)));
+    }
+}
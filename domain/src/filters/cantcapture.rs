@@ -1,243 +1,212 @@
+use crate::filters::context::FilterContext;
 use crate::filters::movefilter::MoveFilter;
-use chess::{ChessMove, Game, Piece};
+use crate::filters::pieceset::PieceSet;
+use chess::{ChessMove, Piece};
 
 pub struct CantCapture {
-    source: Vec<Piece>,
-    target: Vec<Piece>,
+    source: PieceSet,
+    target: PieceSet,
 }
 
 impl CantCapture {
     pub fn only_pawns_or_king_can_capture_pawns() -> Self {
         Self {
-            source: vec![Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen],
-            target: vec![Piece::Pawn],
+            source: PieceSet::of(&[Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]),
+            target: PieceSet::of(&[Piece::Pawn]),
         }
     }
 
     pub fn only_king_can_capture_pawns() -> Self {
         Self {
-            source: vec![
+            source: PieceSet::of(&[
                 Piece::Knight,
                 Piece::Bishop,
                 Piece::Rook,
                 Piece::Queen,
                 Piece::Pawn,
-            ],
-            target: vec![Piece::Pawn],
+            ]),
+            target: PieceSet::of(&[Piece::Pawn]),
         }
     }
 
     pub fn pawns_cant_be_captured() -> Self {
         Self {
-            source: vec![
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::Pawn,
-                Piece::King,
-            ],
-            target: vec![Piece::Pawn],
+            source: PieceSet::ALL,
+            target: PieceSet::of(&[Piece::Pawn]),
         }
     }
 
     pub fn king_cant_capture_anything() -> Self {
         Self {
-            source: vec![Piece::King],
-            target: vec![
+            source: PieceSet::of(&[Piece::King]),
+            target: PieceSet::of(&[
                 Piece::Pawn,
                 Piece::Knight,
                 Piece::Bishop,
                 Piece::Rook,
                 Piece::Queen,
-            ],
+            ]),
         }
     }
 
     pub fn queen_cant_capture_anything() -> Self {
         Self {
-            source: vec![Piece::Queen],
-            target: vec![
+            source: PieceSet::of(&[Piece::Queen]),
+            target: PieceSet::of(&[
                 Piece::Pawn,
                 Piece::Knight,
                 Piece::Bishop,
                 Piece::Rook,
                 Piece::Queen,
-            ],
+            ]),
         }
     }
 
     pub fn rooks_cant_capture_anything() -> Self {
         Self {
-            source: vec![Piece::Rook],
-            target: vec![
+            source: PieceSet::of(&[Piece::Rook]),
+            target: PieceSet::of(&[
                 Piece::Pawn,
                 Piece::Knight,
                 Piece::Bishop,
                 Piece::Rook,
                 Piece::Queen,
-            ],
+            ]),
         }
     }
 
     pub fn bishops_cant_capture_anything() -> Self {
         Self {
-            source: vec![Piece::Bishop],
-            target: vec![
+            source: PieceSet::of(&[Piece::Bishop]),
+            target: PieceSet::of(&[
                 Piece::Pawn,
                 Piece::Knight,
                 Piece::Bishop,
                 Piece::Rook,
                 Piece::Queen,
-            ],
+            ]),
         }
     }
 
     pub fn knights_cant_capture_anything() -> Self {
         Self {
-            source: vec![Piece::Knight],
-            target: vec![
+            source: PieceSet::of(&[Piece::Knight]),
+            target: PieceSet::of(&[
                 Piece::Pawn,
                 Piece::Knight,
                 Piece::Bishop,
                 Piece::Rook,
                 Piece::Queen,
-            ],
+            ]),
         }
     }
 
     pub fn knights_cant_capture_queens() -> Self {
         Self {
-            source: vec![Piece::Knight],
-            target: vec![Piece::Queen],
+            source: PieceSet::of(&[Piece::Knight]),
+            target: PieceSet::of(&[Piece::Queen]),
         }
     }
 
     pub fn bishops_cant_capture_queens() -> Self {
         Self {
-            source: vec![Piece::Bishop],
-            target: vec![Piece::Queen],
+            source: PieceSet::of(&[Piece::Bishop]),
+            target: PieceSet::of(&[Piece::Queen]),
         }
     }
 
     pub fn rooks_cant_capture_queens() -> Self {
         Self {
-            source: vec![Piece::Rook],
-            target: vec![Piece::Queen],
+            source: PieceSet::of(&[Piece::Rook]),
+            target: PieceSet::of(&[Piece::Queen]),
         }
     }
 
     pub fn queen_can_only_capture_pawns() -> Self {
         Self {
-            source: vec![Piece::Queen],
-            target: vec![Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen],
+            source: PieceSet::of(&[Piece::Queen]),
+            target: PieceSet::of(&[Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]),
         }
     }
 
     pub fn queen_can_only_capture_rooks() -> Self {
         Self {
-            source: vec![Piece::Queen],
-            target: vec![Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Queen],
+            source: PieceSet::of(&[Piece::Queen]),
+            target: PieceSet::of(&[Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Queen]),
         }
     }
 
     pub fn queen_can_only_capture_knights() -> Self {
         Self {
-            source: vec![Piece::Queen],
-            target: vec![Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Queen],
+            source: PieceSet::of(&[Piece::Queen]),
+            target: PieceSet::of(&[Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Queen]),
         }
     }
 
     pub fn queen_can_only_capture_bishops() -> Self {
         Self {
-            source: vec![Piece::Queen],
-            target: vec![Piece::Pawn, Piece::Knight, Piece::Rook, Piece::Queen],
+            source: PieceSet::of(&[Piece::Queen]),
+            target: PieceSet::of(&[Piece::Pawn, Piece::Knight, Piece::Rook, Piece::Queen]),
         }
     }
 
     pub fn only_pawns_can_capture_pawns() -> Self {
         Self {
-            source: vec![
+            source: PieceSet::of(&[
                 Piece::Knight,
                 Piece::Bishop,
                 Piece::Rook,
                 Piece::Queen,
                 Piece::King,
-            ],
-            target: vec![Piece::Pawn],
+            ]),
+            target: PieceSet::of(&[Piece::Pawn]),
         }
     }
 
     pub fn pawns_can_only_capture_pawns() -> Self {
         Self {
-            source: vec![Piece::Pawn],
-            target: vec![Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen],
+            source: PieceSet::of(&[Piece::Pawn]),
+            target: PieceSet::of(&[Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]),
         }
     }
 
     pub fn pawns_cant_capture_pawns() -> Self {
         Self {
-            source: vec![Piece::Pawn],
-            target: vec![Piece::Pawn],
+            source: PieceSet::of(&[Piece::Pawn]),
+            target: PieceSet::of(&[Piece::Pawn]),
         }
     }
 
     pub fn cant_capture_rooks() -> Self {
         Self {
-            source: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
-            target: vec![Piece::Rook],
+            source: PieceSet::ALL,
+            target: PieceSet::of(&[Piece::Rook]),
         }
     }
 
     pub fn cant_capture_bishops() -> Self {
         Self {
-            source: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
-            target: vec![Piece::Bishop],
+            source: PieceSet::ALL,
+            target: PieceSet::of(&[Piece::Bishop]),
         }
     }
 
     pub fn cant_capture_knights() -> Self {
         Self {
-            source: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
-            target: vec![Piece::Knight],
+            source: PieceSet::ALL,
+            target: PieceSet::of(&[Piece::Knight]),
         }
     }
 }
 
 impl MoveFilter for CantCapture {
-    fn filter(&self, game: &Game, chess_move: &ChessMove) -> bool {
-        let board = &game.current_position();
-        if self
-            .source
-            .contains(&board.piece_on(chess_move.get_source()).unwrap())
-        {
-            match board.piece_on(chess_move.get_dest()) {
-                Some(piece) => self.target.contains(&piece),
-                None => false,
-            }
-        } else {
-            false
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        let board = &context.board;
+        match board.piece_on(chess_move.get_source()) {
+            Some(piece) if self.source.contains(piece) => board
+                .piece_on(chess_move.get_dest())
+                .is_some_and(|target| self.target.contains(target)),
+            _ => false,
         }
     }
 }
@@ -255,7 +224,12 @@ mod tests {
         game.make_move(ChessMove::new(Square::E2, Square::E4, None));
         game.make_move(ChessMove::new(Square::D7, Square::D5, None));
         let pp_filter = CantCapture::pawns_cant_capture_pawns();
-        assert_eq!(30, pp_filter.filter_moves(&game).len());
+        assert_eq!(
+            30,
+            pp_filter
+                .filter_moves(&FilterContext::from_game(&game))
+                .len()
+        );
     }
 
     #[test]
@@ -263,6 +237,11 @@ mod tests {
     fn king_cant_capture_queen() {
         let game = Game::from_str("8/8/8/8/8/8/q6k/K7 w - - 0 1").expect("wrong");
         let king_filter = CantCapture::king_cant_capture_anything();
-        assert_eq!(0, king_filter.filter_moves(&game).len());
+        assert_eq!(
+            0,
+            king_filter
+                .filter_moves(&FilterContext::from_game(&game))
+                .len()
+        );
     }
 }
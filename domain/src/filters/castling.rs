@@ -0,0 +1,76 @@
+use crate::filters::context::FilterContext;
+use crate::filters::identity::castling_rook;
+use crate::filters::movefilter::MoveFilter;
+use chess::{Board, ChessMove, File, Piece, Square};
+
+pub struct Castling {
+    forbidden: fn(&Board, &ChessMove) -> bool,
+}
+
+impl Castling {
+    pub fn never_castle() -> Self {
+        Self {
+            forbidden: |board, chess_move| castling_rook(board, chess_move).is_some(),
+        }
+    }
+
+    // As long as long castling is possible the king and the a-rook have to wait for it.
+    pub fn must_castle_queenside() -> Self {
+        Self {
+            forbidden: |board, chess_move| {
+                let kingside = castling_rook(board, chess_move)
+                    .is_some_and(|(rook, _)| rook.get_file() == File::H);
+                let rights = board.castle_rights(board.side_to_move());
+                if kingside || !rights.has_queenside() {
+                    return kingside;
+                }
+                let queenside = castling_rook(board, chess_move).is_some();
+                let source = chess_move.get_source();
+                let rook_square = Square::make_square(source.get_rank(), File::A);
+                let spoils_castling =
+                    board.piece_on(source) == Some(Piece::King) || source == rook_square;
+                spoils_castling && !queenside
+            },
+        }
+    }
+}
+
+impl MoveFilter for Castling {
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        (self.forbidden)(&context.board, chess_move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Game;
+    use std::str::FromStr;
+
+    #[test]
+    fn cant_castle() {
+        let game = Game::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let moves = Castling::never_castle().filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::G1, None)));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::C1, None)));
+        assert!(moves.contains(&ChessMove::new(Square::E1, Square::F1, None)));
+    }
+
+    #[test]
+    fn king_waits_for_long_castling() {
+        let game = Game::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let moves =
+            Castling::must_castle_queenside().filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::G1, None)));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::F1, None)));
+        assert!(!moves.contains(&ChessMove::new(Square::A1, Square::B1, None)));
+        assert!(moves.contains(&ChessMove::new(Square::E1, Square::C1, None)));
+        assert!(moves.contains(&ChessMove::new(Square::H1, Square::H2, None)));
+        // Without the right to castle long the king is free, except for short castling.
+        let game = Game::from_str("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1").unwrap();
+        let moves =
+            Castling::must_castle_queenside().filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::G1, None)));
+        assert!(moves.contains(&ChessMove::new(Square::E1, Square::F1, None)));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::clock::{now_ms, Clock};
+use crate::filters::history::{captured_piece, History};
+use crate::filters::material::value;
+use chess::{Board, Color, Game, MoveGen};
+
+// Everything a rule may look at, worked out once per position instead of once for every move.
+pub struct FilterContext {
+    pub board: Board,
+    pub side: Color,
+    // Moves played so far by both colors, bonus moves included.
+    pub ply: u16,
+    // The number of the move about to be played, starting at 1 for both colors.
+    pub turn: u16,
+    // Moves played so far by the side to move.
+    pub own_moves: u16,
+    pub history: History,
+    // Material value taken by white and black, in Color::to_index order.
+    pub captured: [u32; 2],
+    pub clock: Option<Clock>,
+    // When the context was made, the time the clock is read at.
+    pub now: u64,
+}
+
+impl FilterContext {
+    pub fn new(history: History, clock: Option<Clock>) -> Self {
+        let board = *history.board();
+        let side = board.side_to_move();
+        let mut captured = [0; 2];
+        for (before, chess_move) in history.moves() {
+            if let Some(piece) = captured_piece(before, chess_move) {
+                captured[before.side_to_move().to_index()] += value(piece) as u32;
+            }
+        }
+        Self {
+            board,
+            side,
+            ply: history.len() as u16,
+            turn: history.len() as u16 / 2 + 1,
+            own_moves: history.moves_by(side).count() as u16,
+            history,
+            captured,
+            clock,
+            now: now_ms(),
+        }
+    }
+
+    // For a game without a clock that started from the normal starting position, or one that
+    // has no moves yet, like a game set up from a FEN.
+    pub fn from_game(game: &Game) -> Self {
+        let start = if game.actions().is_empty() {
+            game.current_position()
+        } else {
+            Board::default()
+        };
+        Self::new(History::new(start, game), None)
+    }
+
+    pub fn legal_moves(&self) -> MoveGen {
+        MoveGen::new_legal(&self.board)
+    }
+
+    // Material value this color has taken from the opponent.
+    pub fn captured_by(&self, color: Color) -> u32 {
+        self.captured[color.to_index()]
+    }
+
+    pub fn time_left(&self, color: Color) -> Option<u64> {
+        self.clock
+            .map(|clock| clock.remaining(color, self.side, self.now))
+    }
+
+    // For a position set up from a FEN with this fullmove number, the moves before it count
+    // towards the turn. They aren't in the history, so rules looking at it don't see them.
+    pub fn starting_at_move(mut self, fullmove: u16) -> Self {
+        let earlier = fullmove.saturating_sub(1);
+        let black_started = self.history.start().side_to_move() == Color::Black;
+        self.ply += 2 * earlier + black_started as u16;
+        self.turn = self.ply / 2 + 1;
+        self.own_moves += earlier + (black_started && self.side == Color::White) as u16;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bonus::{play, Bonus, BonusMove};
+    use crate::gamestate::GameState;
+    use chess::{ChessMove, Square};
+
+    #[test]
+    fn counts_turns_and_captures() {
+        let mut game = Game::new();
+        for (source, dest) in [
+            (Square::E2, Square::E4),
+            (Square::D7, Square::D5),
+            (Square::E4, Square::D5),
+        ] {
+            game.make_move(ChessMove::new(source, dest, None));
+        }
+        game.offer_draw(Color::White);
+        let context = FilterContext::from_game(&game);
+        assert_eq!(context.side, Color::Black);
+        assert_eq!(context.ply, 3);
+        assert_eq!(context.turn, 2);
+        assert_eq!(context.own_moves, 1);
+        assert_eq!(context.captured_by(Color::White), 1);
+        assert_eq!(context.captured_by(Color::Black), 0);
+        assert_eq!(context.time_left(Color::White), None);
+    }
+
+    #[test]
+    fn bonus_moves_are_part_of_the_history() {
+        let mut earlier = GameState::new();
+        earlier.make_move(ChessMove::new(Square::E2, Square::E3, None));
+        earlier.make_move(ChessMove::new(Square::E7, Square::E6, None));
+        let bonus_move = BonusMove::parse(Bonus::LongPawnStep, "+e3e5").unwrap();
+        let mut game =
+            GameState::new_with_board(play(&earlier.current_position(), &bonus_move).unwrap());
+        game.make_move(ChessMove::new(Square::D7, Square::D6, None));
+        let history = History::with_bonus_moves(&[(earlier, bonus_move)], &game);
+        let context = FilterContext::new(history, None);
+        assert_eq!(context.ply, 4);
+        assert_eq!(context.own_moves, 2);
+        assert_eq!(context.board, game.current_position());
+    }
+}
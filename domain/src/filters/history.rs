@@ -0,0 +1,120 @@
+use crate::bonus::BonusMove;
+use crate::gamestate::GameState;
+use chess::{Action, Board, ChessMove, Color, Game, Piece};
+
+// Every move played so far, together with the position it was played in,
+// so rules can count whatever they need without replaying the game themselves.
+// Games don't always start from the normal starting position, think of material odds.
+// The default is the normal starting position without moves.
+#[derive(Clone, Default)]
+pub struct History {
+    start: Board,
+    moves: Vec<(Board, ChessMove)>,
+    board: Board,
+}
+
+impl History {
+    pub fn new(start: Board, game: &Game) -> Self {
+        let mut history = Self {
+            start,
+            moves: Vec::new(),
+            board: start,
+        };
+        for action in game.actions() {
+            if let Action::MakeMove(chess_move) = action {
+                history.moves.push((history.board, *chess_move));
+                history.board = history.board.make_move_new(*chess_move);
+            }
+        }
+        history
+    }
+
+    // A game with bonus moves is split into a GameState per part, see ChessGame. A bonus move
+    // is kept as the chess move from its source to its destination. The parts already know
+    // their moves, so nothing has to be replayed.
+    pub fn with_bonus_moves(earlier_games: &[(GameState, BonusMove)], game: &GameState) -> Self {
+        let start = match earlier_games.first() {
+            Some((first, _)) => first.start_position(),
+            None => game.start_position(),
+        };
+        let mut moves = Vec::new();
+        for (earlier, bonus_move) in earlier_games {
+            moves.extend_from_slice(earlier.moves());
+            let chess_move = ChessMove::new(bonus_move.source, bonus_move.dest, None);
+            moves.push((earlier.current_position(), chess_move));
+        }
+        moves.extend_from_slice(game.moves());
+        Self {
+            start,
+            moves,
+            board: game.current_position(),
+        }
+    }
+
+    // Plays a move on top of the history, for looking ahead like an engine does.
+    pub fn push(&mut self, chess_move: ChessMove) {
+        self.moves.push((self.board, chess_move));
+        self.board = self.board.make_move_new(chess_move);
+    }
+
+    // Takes back the last move, so one history can be searched move by move.
+    pub fn pop(&mut self) -> Option<ChessMove> {
+        let (board, chess_move) = self.moves.pop()?;
+        self.board = board;
+        Some(chess_move)
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    // The position after the last move.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn moves(&self) -> impl Iterator<Item = &(Board, ChessMove)> {
+        self.moves.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    // The moves played by this color, with the position before each of them.
+    pub fn moves_by(&self, color: Color) -> impl Iterator<Item = &(Board, ChessMove)> {
+        self.moves()
+            .filter(move |(board, _)| board.side_to_move() == color)
+    }
+
+    pub fn count(&self, color: Color, counts: fn(&Board, &ChessMove) -> bool) -> u16 {
+        self.moves_by(color)
+            .filter(|(board, chess_move)| counts(board, chess_move))
+            .count() as u16
+    }
+
+    pub fn last_move(&self, color: Color) -> Option<&ChessMove> {
+        self.moves_by(color)
+            .last()
+            .map(|(_, chess_move)| chess_move)
+    }
+}
+
+// En passant is the only capture that doesn't land on a piece.
+pub fn is_capture(board: &Board, chess_move: &ChessMove) -> bool {
+    board.piece_on(chess_move.get_dest()).is_some()
+        || (board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            && chess_move.get_source().get_file() != chess_move.get_dest().get_file())
+}
+
+// The piece this move takes off the board, if any.
+pub fn captured_piece(board: &Board, chess_move: &ChessMove) -> Option<Piece> {
+    match board.piece_on(chess_move.get_dest()) {
+        Some(piece) => Some(piece),
+        None => is_capture(board, chess_move).then_some(Piece::Pawn),
+    }
+}
@@ -0,0 +1,170 @@
+use crate::filters::history::History;
+use chess::{Board, ChessMove, Color, File, Piece, Rank, Square, ALL_SQUARES};
+
+// Follows every piece from the square it started on through the game, because the board
+// only knows which kind of piece stands where. Pieces are identified by their starting square.
+pub struct PieceTracker {
+    origins: [Option<Square>; 64],
+    promoted: Vec<Square>,
+}
+
+impl PieceTracker {
+    pub fn new(history: &History) -> Self {
+        let mut tracker = Self::starting_position(history.start());
+        for (board, chess_move) in history.moves() {
+            tracker.play(board, chess_move);
+        }
+        tracker
+    }
+
+    fn starting_position(board: &Board) -> Self {
+        let mut origins = [None; 64];
+        for square in *board.combined() {
+            origins[square.to_index()] = Some(square);
+        }
+        Self {
+            origins,
+            promoted: Vec::new(),
+        }
+    }
+
+    fn play(&mut self, board: &Board, chess_move: &ChessMove) {
+        let source = chess_move.get_source();
+        let dest = chess_move.get_dest();
+        if let Some(captured) = en_passant_capture(board, chess_move) {
+            self.origins[captured.to_index()] = None;
+        }
+        if let Some((rook_source, rook_dest)) = castling_rook(board, chess_move) {
+            self.origins[rook_dest.to_index()] = self.origins[rook_source.to_index()].take();
+        }
+        let origin = self.origins[source.to_index()].take();
+        if let (Some(origin), Some(_)) = (origin, chess_move.get_promotion()) {
+            self.promoted.push(origin);
+        }
+        self.origins[dest.to_index()] = origin;
+    }
+
+    // Where the piece that started here was at the start of the game.
+    pub fn origin(&self, square: Square) -> Option<Square> {
+        self.origins[square.to_index()]
+    }
+
+    // Where the piece that started here is now, None once it has been captured.
+    pub fn square_of(&self, origin: Square) -> Option<Square> {
+        ALL_SQUARES
+            .into_iter()
+            .find(|square| self.origin(*square) == Some(origin))
+    }
+
+    pub fn has_promoted(&self, origin: Square) -> bool {
+        self.promoted.contains(&origin)
+    }
+}
+
+// The square of the pawn that is taken en passant, which isn't the square the capture lands on.
+fn en_passant_capture(board: &Board, chess_move: &ChessMove) -> Option<Square> {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let is_en_passant = board.piece_on(source) == Some(Piece::Pawn)
+        && source.get_file() != dest.get_file()
+        && board.piece_on(dest).is_none();
+    is_en_passant.then(|| Square::make_square(source.get_rank(), dest.get_file()))
+}
+
+// The rook's move when this move castles.
+pub fn castling_rook(board: &Board, chess_move: &ChessMove) -> Option<(Square, Square)> {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    if board.piece_on(source) != Some(Piece::King)
+        || source
+            .get_file()
+            .to_index()
+            .abs_diff(dest.get_file().to_index())
+            != 2
+    {
+        return None;
+    }
+    let rank = source.get_rank();
+    match dest.get_file() {
+        File::G => Some((
+            Square::make_square(rank, File::H),
+            Square::make_square(rank, File::F),
+        )),
+        _ => Some((
+            Square::make_square(rank, File::A),
+            Square::make_square(rank, File::D),
+        )),
+    }
+}
+
+// Starting squares are given from white's side, black's pieces start on the mirrored square.
+pub fn starting_square(white_square: Square, color: Color) -> Square {
+    match color {
+        Color::White => white_square,
+        Color::Black => Square::make_square(
+            Rank::from_index(7 - white_square.get_rank().to_index()),
+            white_square.get_file(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Game;
+
+    fn track(moves: &[(Square, Square, Option<Piece>)]) -> PieceTracker {
+        let mut game = Game::new();
+        for (source, dest, promotion) in moves {
+            game.make_move(ChessMove::new(*source, *dest, *promotion));
+        }
+        PieceTracker::new(&History::new(Board::default(), &game))
+    }
+
+    #[test]
+    fn follows_castling() {
+        let tracker = track(&[
+            (Square::E2, Square::E4, None),
+            (Square::E7, Square::E5, None),
+            (Square::G1, Square::F3, None),
+            (Square::B8, Square::C6, None),
+            (Square::F1, Square::C4, None),
+            (Square::G8, Square::F6, None),
+            (Square::E1, Square::G1, None),
+        ]);
+        assert_eq!(tracker.square_of(Square::H1), Some(Square::F1));
+        assert_eq!(tracker.square_of(Square::E1), Some(Square::G1));
+        assert_eq!(tracker.origin(Square::F3), Some(Square::G1));
+    }
+
+    #[test]
+    fn follows_en_passant() {
+        let tracker = track(&[
+            (Square::E2, Square::E4, None),
+            (Square::A7, Square::A6, None),
+            (Square::E4, Square::E5, None),
+            (Square::D7, Square::D5, None),
+            (Square::E5, Square::D6, None),
+        ]);
+        assert_eq!(tracker.square_of(Square::D7), None);
+        assert_eq!(tracker.square_of(Square::E2), Some(Square::D6));
+    }
+
+    #[test]
+    fn follows_promotion() {
+        let tracker = track(&[
+            (Square::H2, Square::H4, None),
+            (Square::G7, Square::G5, None),
+            (Square::H4, Square::G5, None),
+            (Square::G8, Square::F6, None),
+            (Square::G5, Square::G6, None),
+            (Square::F6, Square::E4, None),
+            (Square::G6, Square::G7, None),
+            (Square::E4, Square::D6, None),
+            (Square::G7, Square::H8, Some(Piece::Queen)),
+        ]);
+        assert!(tracker.has_promoted(Square::H2));
+        assert_eq!(tracker.square_of(Square::H2), Some(Square::H8));
+        assert_eq!(tracker.square_of(Square::H8), None);
+    }
+}
@@ -0,0 +1,22 @@
+use chess::{Board, Color, Piece, ALL_PIECES};
+
+// Standard piece values. The king can't be traded, so it isn't counted.
+pub fn value(piece: Piece) -> u8 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight | Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 0,
+    }
+}
+
+pub fn material(board: &Board, color: Color) -> u32 {
+    ALL_PIECES
+        .iter()
+        .map(|&piece| {
+            let count = (board.pieces(piece) & board.color_combined(color)).popcnt();
+            count * value(piece) as u32
+        })
+        .sum()
+}
@@ -1,5 +1,6 @@
+use crate::filters::context::FilterContext;
 use crate::filters::movefilter::MoveFilter;
-use chess::{Action, ChessMove, Game, Piece};
+use chess::{ChessMove, Piece};
 
 pub struct MoveAfter {
     piece: Piece,
@@ -93,20 +94,12 @@ impl MoveAfter {
 }
 
 impl MoveFilter for MoveAfter {
-    fn filter(&self, game: &Game, chess_move: &ChessMove) -> bool {
-        let board = &game.current_position();
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        let board = &context.board;
         if board.piece_on(chess_move.get_source()).unwrap() != self.piece {
             return false;
         }
-        let mut counter: u16 = 2;
-        for action in game.actions() {
-            match action {
-                Action::MakeMove(_) => counter += 1,
-                _ => {}
-            }
-        }
-        let turn = counter / 2;
-        turn > self.turn
+        context.turn > self.turn
     }
 }
 
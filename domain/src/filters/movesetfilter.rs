@@ -0,0 +1,14 @@
+use crate::filters::context::FilterContext;
+use chess::ChessMove;
+
+// Generic trait for EloStealo rules that can only be decided by looking at every legal move,
+// like having to capture whenever you can.
+// Implementations get all legal moves in regular chess and return the ones that are allowed.
+pub trait MoveSetFilter {
+    fn filter_set(&self, context: &FilterContext, moves: Vec<ChessMove>) -> Vec<ChessMove>;
+
+    fn filter_moves(&self, context: &FilterContext) -> Vec<ChessMove> {
+        let moves = context.legal_moves().collect();
+        Self::filter_set(self, context, moves)
+    }
+}
@@ -1,183 +1,152 @@
+use crate::filters::context::FilterContext;
 use crate::filters::movefilter::MoveFilter;
-use chess::{ChessMove, Game, Piece, Square};
-use std::iter::Iterator;
+use crate::filters::pieceset::PieceSet;
+use chess::{get_file, BitBoard, ChessMove, File, Piece, EMPTY};
 
-// Prohibited squares are passed as numbers 0-63 to work with ranges
+// Squares that can't be moved to as a mask, only for the given pieces.
 pub struct MoveTo {
-    square_indexes: Vec<u8>,
-    pieces: Vec<Piece>,
+    squares: BitBoard,
+    pieces: PieceSet,
 }
 
-impl MoveTo {
+// a1 is a dark square, so the light squares start at b1.
+const LIGHT_SQUARES: BitBoard = BitBoard(0x55AA_55AA_55AA_55AA);
+const DARK_SQUARES: BitBoard = BitBoard(0xAA55_AA55_AA55_AA55);
+const INNER_SQUARES: BitBoard = BitBoard(0x007E_7E7E_7E7E_7E00);
+
+fn files(files: &[File]) -> BitBoard {
+    files
+        .iter()
+        .fold(EMPTY, |squares, file| squares | get_file(*file))
+}
 
+impl MoveTo {
     pub fn rooks_can_only_move_to_the_edges() -> Self {
         Self {
-            square_indexes: (0..64)
-                .filter(|&x| x % 8 != 0 && (x + 1) % 8 != 0 && x > 7 && x < 56)
-                .collect(),
-            pieces: vec![Piece::Rook],
+            squares: INNER_SQUARES,
+            pieces: PieceSet::of(&[Piece::Rook]),
         }
     }
 
     pub fn king_can_only_move_to_dark_squares() -> Self {
         Self {
-            square_indexes: (0..32).map(|x| 1 + x * 2 + (x % 8) / 4).collect(),
-            pieces: vec![Piece::King],
+            squares: LIGHT_SQUARES,
+            pieces: PieceSet::of(&[Piece::King]),
         }
     }
 
     pub fn king_can_only_move_to_light_squares() -> Self {
         Self {
-            square_indexes: (0..32).map(|x| x * 2 + (x % 8) / 4).collect(),
-            pieces: vec![Piece::King],
+            squares: DARK_SQUARES,
+            pieces: PieceSet::of(&[Piece::King]),
         }
     }
 
     pub fn queen_can_only_move_to_dark_squares() -> Self {
         Self {
-            square_indexes: (0..32).map(|x| 1 + x * 2 + (x % 8) / 4).collect(),
-            pieces: vec![Piece::Queen],
+            squares: LIGHT_SQUARES,
+            pieces: PieceSet::of(&[Piece::Queen]),
         }
     }
 
     pub fn queen_can_only_move_to_light_squares() -> Self {
         Self {
-            square_indexes: (0..32).map(|x| x * 2 + (x % 8) / 4).collect(),
-            pieces: vec![Piece::Queen],
+            squares: DARK_SQUARES,
+            pieces: PieceSet::of(&[Piece::Queen]),
         }
     }
 
     pub fn cant_play_on_the_c_file() -> Self {
         Self {
-            square_indexes: (0..8).map(|x| x * 8 + 2).collect(),
-            pieces: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
+            squares: files(&[File::C]),
+            pieces: PieceSet::ALL,
         }
     }
 
     pub fn cant_play_on_the_h_file() -> Self {
         Self {
-            square_indexes: (0..8).map(|x| x * 8 + 7).collect(),
-            pieces: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
+            squares: files(&[File::H]),
+            pieces: PieceSet::ALL,
         }
     }
 
     pub fn cant_play_on_the_e_file() -> Self {
         Self {
-            square_indexes: (0..8).map(|x| x * 8 + 4).collect(),
-            pieces: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
+            squares: files(&[File::E]),
+            pieces: PieceSet::ALL,
         }
     }
 
     pub fn cant_play_on_the_e_or_h_file() -> Self {
         Self {
-            square_indexes: (0..63).filter(|&x| x % 8 == 4 || x % 8 == 7).collect(),
-            pieces: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
+            squares: files(&[File::E, File::H]),
+            pieces: PieceSet::ALL,
         }
     }
 
     pub fn cant_play_on_the_c_or_e_file() -> Self {
         Self {
-            square_indexes: (0..63).filter(|&x| x % 8 == 2 || x % 8 == 4).collect(),
-            pieces: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
+            squares: files(&[File::C, File::E]),
+            pieces: PieceSet::ALL,
         }
     }
 
     pub fn cant_play_on_the_c_or_h_file() -> Self {
         Self {
-            square_indexes: (0..63).filter(|&x| x % 8 == 2 || x % 8 == 7).collect(),
-            pieces: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
+            squares: files(&[File::C, File::H]),
+            pieces: PieceSet::ALL,
         }
     }
 
     pub fn cant_play_on_the_c_or_e_or_h_file() -> Self {
         Self {
-            square_indexes: (0..63)
-                .filter(|&x| x % 8 == 4 || x % 8 == 7 || x % 8 == 2)
-                .collect(),
-            pieces: vec![
-                Piece::Pawn,
-                Piece::Knight,
-                Piece::Bishop,
-                Piece::Rook,
-                Piece::Queen,
-                Piece::King,
-            ],
+            squares: files(&[File::C, File::E, File::H]),
+            pieces: PieceSet::ALL,
         }
     }
 }
 
 impl MoveFilter for MoveTo {
-    fn filter(&self, game: &Game, chess_move: &ChessMove) -> bool {
-        let board = &game.current_position();
-        let squares: Vec<Square> = unsafe {
-            self.square_indexes
-                .iter()
-                .map(|&x| Square::new(x))
-                .collect()
-        };
-        if !self
-            .pieces
-            .contains(&board.piece_on(chess_move.get_source()).unwrap())
-        {
-            return false;
-        }
-        if squares.contains(&chess_move.get_dest()) {
-            return true;
-        }
-        false
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        let moves_piece = context
+            .board
+            .piece_on(chess_move.get_source())
+            .is_some_and(|piece| self.pieces.contains(piece));
+        moves_piece && self.squares & BitBoard::from_square(chess_move.get_dest()) != EMPTY
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chess::{Game, Square};
+    use std::str::FromStr;
 
     #[test]
     fn forbidden_a_pawn() {
         let game = Game::new();
         let c_filter = MoveTo::cant_play_on_the_c_file();
-        assert_eq!(17, c_filter.filter_moves(&game).len());
+        assert_eq!(
+            17,
+            c_filter
+                .filter_moves(&FilterContext::from_game(&game))
+                .len()
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rook_reaches_every_edge() {
+        let game = Game::from_str("7k/8/8/8/3R4/8/8/K7 w - - 0 1").unwrap();
+        let filter = MoveTo::rooks_can_only_move_to_the_edges();
+        let rook_moves: Vec<Square> = filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .iter()
+            .filter(|chess_move| chess_move.get_source() == Square::D4)
+            .map(|chess_move| chess_move.get_dest())
+            .collect();
+        assert_eq!(
+            rook_moves,
+            vec![Square::D1, Square::A4, Square::H4, Square::D8]
+        );
+    }
+}
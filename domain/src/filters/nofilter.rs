@@ -1,6 +1,8 @@
+use crate::filters::context::FilterContext;
 use crate::filters::movefilter::MoveFilter;
-use chess::{ChessMove, Game};
+use chess::ChessMove;
 
+#[derive(Default)]
 pub struct NoFilter {}
 
 impl NoFilter {
@@ -10,7 +12,7 @@ impl NoFilter {
 }
 
 impl MoveFilter for NoFilter {
-    fn filter(&self, _: &Game, _: &ChessMove) -> bool {
+    fn filter(&self, _: &FilterContext, _: &ChessMove) -> bool {
         false
     }
 }
@@ -24,6 +26,11 @@ mod tests {
     fn new_game_test() {
         let game = Game::new();
         let no_filter = NoFilter::new();
-        assert_eq!(no_filter.filter_moves(&game).len(), 20);
+        assert_eq!(
+            no_filter
+                .filter_moves(&FilterContext::from_game(&game))
+                .len(),
+            20
+        );
     }
 }
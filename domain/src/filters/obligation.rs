@@ -0,0 +1,378 @@
+use crate::filters::context::FilterContext;
+use crate::filters::history::History;
+use crate::filters::identity::castling_rook;
+use crate::filters::movesetfilter::MoveSetFilter;
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_pawn_quiets,
+    get_rook_moves, BitBoard, Board, CastleRights, ChessMove, Color, Piece, Square, EMPTY,
+};
+
+// Something the player has to have done by the deadline.
+pub enum Goal {
+    // This many own pieces of a kind on the squares, given from white's side. Only the pieces
+    // still on the board count, so losing one doesn't make the goal impossible.
+    Placement {
+        piece: Piece,
+        squares: BitBoard,
+        count: u8,
+    },
+    // A move like this has been played, while possible says it can still be played later.
+    Move {
+        played: fn(&Board, &ChessMove) -> bool,
+        possible: fn(&Board, Color) -> bool,
+    },
+}
+
+impl Goal {
+    fn size(&self, board: &Board, color: Color) -> u8 {
+        match self {
+            Goal::Placement { piece, count, .. } => {
+                let available = (board.pieces(*piece) & board.color_combined(color)).popcnt();
+                (*count).min(available as u8)
+            }
+            Goal::Move { .. } => 1,
+        }
+    }
+
+    // The pieces or moves still missing.
+    fn missing(&self, board: &Board, color: Color, played: &[(Board, ChessMove)]) -> u8 {
+        match self {
+            Goal::Placement { piece, squares, .. } => {
+                let placed = board.pieces(*piece)
+                    & board.color_combined(color)
+                    & relative_squares(*squares, color);
+                self.size(board, color)
+                    .saturating_sub(placed.popcnt() as u8)
+            }
+            Goal::Move { played: counts, .. } => !played
+                .iter()
+                .any(|(board, chess_move)| counts(board, chess_move))
+                as u8,
+        }
+    }
+
+    // At least how many moves the player needs to reach the goal, None when it can't be reached
+    // anymore. A missing piece needs the moves it would take on an empty board, the closest
+    // pieces go.
+    fn moves_needed(
+        &self,
+        board: &Board,
+        color: Color,
+        played: &[(Board, ChessMove)],
+    ) -> Option<u16> {
+        let missing = self.missing(board, color, played) as usize;
+        match self {
+            _ if missing == 0 => Some(0),
+            Goal::Placement { piece, squares, .. } => {
+                let squares = relative_squares(*squares, color);
+                let outside = board.pieces(*piece) & board.color_combined(color) & !squares;
+                let mut distances: Vec<u16> = outside
+                    .filter_map(|square| moves_to(*piece, square, color, squares))
+                    .collect();
+                if distances.len() < missing {
+                    return None;
+                }
+                distances.sort_unstable();
+                Some(distances[..missing].iter().sum())
+            }
+            Goal::Move { possible, .. } => possible(board, color).then_some(1),
+        }
+    }
+}
+
+// How many moves the piece needs to get from the square into the squares on an empty board,
+// None when it never can, like a bishop to the other colour.
+fn moves_to(piece: Piece, from: Square, color: Color, squares: BitBoard) -> Option<u16> {
+    let mut seen = BitBoard::from_square(from);
+    let mut frontier = seen;
+    let mut moves = 0;
+    while frontier & squares == EMPTY {
+        let next = frontier.fold(EMPTY, |next, square| next | reach(piece, square, color)) & !seen;
+        if next == EMPTY {
+            return None;
+        }
+        seen |= next;
+        frontier = next;
+        moves += 1;
+    }
+    Some(moves)
+}
+
+// The squares a piece could move to on an empty board, where a pawn may also capture.
+fn reach(piece: Piece, square: Square, color: Color) -> BitBoard {
+    match piece {
+        Piece::Pawn if square.get_rank() == color.to_their_backrank() => EMPTY,
+        Piece::Pawn => {
+            get_pawn_attacks(square, color, !EMPTY) | get_pawn_quiets(square, color, EMPTY)
+        }
+        Piece::Knight => get_knight_moves(square),
+        Piece::Bishop => get_bishop_moves(square, EMPTY),
+        Piece::Rook => get_rook_moves(square, EMPTY),
+        Piece::Queen => get_bishop_moves(square, EMPTY) | get_rook_moves(square, EMPTY),
+        Piece::King => get_king_moves(square),
+    }
+}
+
+// Black's squares are the white ones mirrored, which is the order of the ranks reversed.
+fn relative_squares(squares: BitBoard, color: Color) -> BitBoard {
+    match color {
+        Color::White => squares,
+        Color::Black => BitBoard::new(squares.0.swap_bytes()),
+    }
+}
+
+// How far the player is with the obligation, for showing it next to the rule.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    pub done: u8,
+    pub total: u8,
+    pub deadline: u16,
+}
+
+// Goals that have to be reached by the player's move with the deadline's number, in any order.
+// Moves are only forbidden when the moves left before the deadline can't make up for what is
+// still missing after them, or when they make a goal unreachable. Placements have to hold after
+// the deadline move, when the player can't finish in time there are no moves left and the game
+// is lost.
+pub struct Obligation {
+    goals: Vec<Goal>,
+    deadline: u16,
+}
+
+impl Obligation {
+    pub fn both_rooks_to_the_third_rank() -> Self {
+        Self {
+            goals: vec![Goal::Placement {
+                piece: Piece::Rook,
+                squares: BitBoard::new(0xff << 16),
+                count: 2,
+            }],
+            deadline: 10,
+        }
+    }
+
+    pub fn both_knights_on_the_rim() -> Self {
+        Self {
+            goals: vec![Goal::Placement {
+                piece: Piece::Knight,
+                squares: BitBoard::new(0x8181_8181_8181_8181),
+                count: 2,
+            }],
+            deadline: 6,
+        }
+    }
+
+    pub fn castle_and_develop() -> Self {
+        let developed = !BitBoard::new(0xff);
+        Self {
+            goals: vec![
+                Goal::Move {
+                    played: |board, chess_move| castling_rook(board, chess_move).is_some(),
+                    possible: |board, color| board.castle_rights(color) != CastleRights::NoRights,
+                },
+                Goal::Placement {
+                    piece: Piece::Knight,
+                    squares: developed,
+                    count: 2,
+                },
+                Goal::Placement {
+                    piece: Piece::Bishop,
+                    squares: developed,
+                    count: 2,
+                },
+            ],
+            deadline: 10,
+        }
+    }
+
+    fn missing(&self, board: &Board, color: Color, played: &[(Board, ChessMove)]) -> u16 {
+        self.goals
+            .iter()
+            .map(|goal| goal.missing(board, color, played) as u16)
+            .sum()
+    }
+
+    fn moves_needed(
+        &self,
+        board: &Board,
+        color: Color,
+        played: &[(Board, ChessMove)],
+    ) -> Option<u16> {
+        self.goals
+            .iter()
+            .map(|goal| goal.moves_needed(board, color, played))
+            .sum()
+    }
+
+    pub fn progress(&self, history: &History, board: &Board, color: Color) -> Progress {
+        let played: Vec<(Board, ChessMove)> = history.moves_by(color).copied().collect();
+        let total: u16 = self
+            .goals
+            .iter()
+            .map(|goal| goal.size(board, color) as u16)
+            .sum();
+        Progress {
+            done: total.saturating_sub(self.missing(board, color, &played)) as u8,
+            total: total as u8,
+            deadline: self.deadline,
+        }
+    }
+}
+
+impl MoveSetFilter for Obligation {
+    fn filter_set(&self, context: &FilterContext, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+        let turn = context.own_moves + 1;
+        if turn > self.deadline {
+            return moves;
+        }
+        let color = context.side;
+        let mut played: Vec<(Board, ChessMove)> =
+            context.history.moves_by(color).copied().collect();
+        moves
+            .into_iter()
+            .filter(|chess_move| {
+                played.push((context.board, *chess_move));
+                let after = context.board.make_move_new(*chess_move);
+                let needed = self.moves_needed(&after, color, &played);
+                played.pop();
+                needed.is_some_and(|needed| needed <= self.deadline - turn)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Game, Square};
+
+    fn play(moves: &[(Square, Square)]) -> Game {
+        let mut game = Game::new();
+        for (source, dest) in moves {
+            game.make_move(ChessMove::new(*source, *dest, None));
+        }
+        game
+    }
+
+    #[test]
+    fn any_order_until_time_runs_out() {
+        let rule = Obligation::both_knights_on_the_rim();
+        // Four moves left and two knights to go, anything goes.
+        let game = play(&[(Square::E2, Square::E4), (Square::E7, Square::E5)]);
+        assert_eq!(
+            rule.filter_moves(&FilterContext::from_game(&game)).len(),
+            29
+        );
+        let game = play(&[
+            (Square::E2, Square::E4),
+            (Square::E7, Square::E5),
+            (Square::D2, Square::D4),
+            (Square::D7, Square::D5),
+            (Square::C2, Square::C4),
+            (Square::C7, Square::C5),
+            (Square::B2, Square::B4),
+            (Square::B7, Square::B5),
+        ]);
+        // Two moves left for two knights, only the knight moves to the rim are allowed.
+        let moves = rule.filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(
+            moves,
+            vec![
+                ChessMove::new(Square::B1, Square::A3, None),
+                ChessMove::new(Square::G1, Square::H3, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn missed_deadline_leaves_no_moves() {
+        let rule = Obligation::both_knights_on_the_rim();
+        // White's knights only went back and forth, on the sixth move one can't reach the rim.
+        let mut game = Game::new();
+        for _ in 0..2 {
+            for chess_move in [
+                (Square::G1, Square::F3),
+                (Square::G8, Square::F6),
+                (Square::F3, Square::G1),
+                (Square::F6, Square::G8),
+            ] {
+                game.make_move(ChessMove::new(chess_move.0, chess_move.1, None));
+            }
+        }
+        game.make_move(ChessMove::new(Square::G1, Square::F3, None));
+        game.make_move(ChessMove::new(Square::G8, Square::F6, None));
+        assert!(rule
+            .filter_moves(&FilterContext::from_game(&game))
+            .is_empty());
+    }
+
+    #[test]
+    fn knight_cant_stray_from_the_rim() {
+        // Stealo 86: from d4 the knight can't reach the rim with the one move left after it.
+        let rule = Obligation::both_knights_on_the_rim();
+        let game = play(&[
+            (Square::B1, Square::A3),
+            (Square::E7, Square::E6),
+            (Square::G1, Square::F3),
+            (Square::D7, Square::D6),
+            (Square::E2, Square::E3),
+            (Square::C7, Square::C6),
+            (Square::D2, Square::D3),
+            (Square::B7, Square::B6),
+        ]);
+        let moves = rule.filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::F3, Square::D4, None)));
+        assert!(moves.contains(&ChessMove::new(Square::F3, Square::H4, None)));
+        assert!(moves.contains(&ChessMove::new(Square::H2, Square::H3, None)));
+    }
+
+    #[test]
+    fn castling_has_to_stay_possible() {
+        // Stealo 87: after 2.Ke2 castling can't be done by the deadline anymore.
+        let rule = Obligation::castle_and_develop();
+        let game = play(&[(Square::E2, Square::E4), (Square::E7, Square::E5)]);
+        let moves = rule.filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::E2, None)));
+        assert!(moves.contains(&ChessMove::new(Square::G1, Square::F3, None)));
+        let game = play(&[
+            (Square::E2, Square::E4),
+            (Square::E7, Square::E5),
+            (Square::E1, Square::E2),
+            (Square::E8, Square::E7),
+        ]);
+        assert!(rule
+            .filter_moves(&FilterContext::from_game(&game))
+            .is_empty());
+    }
+
+    #[test]
+    fn progress_counts_placed_pieces() {
+        let rule = Obligation::both_knights_on_the_rim();
+        let game = play(&[(Square::B1, Square::A3), (Square::G8, Square::H6)]);
+        let history = History::new(Board::default(), &game);
+        let board = game.current_position();
+        let progress = rule.progress(&history, &board, Color::Black);
+        assert_eq!(progress.done, 1);
+        assert_eq!(progress.total, 2);
+        assert_eq!(progress.deadline, 6);
+    }
+
+    #[test]
+    fn castling_counts_as_a_goal() {
+        let rule = Obligation::castle_and_develop();
+        let game = play(&[
+            (Square::E2, Square::E4),
+            (Square::E7, Square::E5),
+            (Square::G1, Square::F3),
+            (Square::B8, Square::C6),
+            (Square::F1, Square::C4),
+            (Square::G8, Square::F6),
+            (Square::E1, Square::G1),
+        ]);
+        let history = History::new(Board::default(), &game);
+        let board = game.current_position();
+        let progress = rule.progress(&history, &board, Color::White);
+        assert_eq!(progress.done, 3);
+        assert_eq!(progress.total, 5);
+    }
+}
@@ -1,197 +1,357 @@
+use crate::filters::context::FilterContext;
 use crate::filters::movefilter::MoveFilter;
-use chess::{ChessMove, Game, Square};
+use chess::{ChessMove, Color, Square};
 
+// What happens when the next move of the sequence isn't legal, for example because the
+// opponent put a piece in the way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Blocked {
+    // Continue with the first step of the sequence that can be played.
+    Skip,
+    // Any move is allowed this turn, the sequence continues with the next step after it.
+    AllowAny,
+    // No move is allowed, so the game is lost.
+    Forfeit,
+}
+
+// Forces the first moves of both colors. Every color follows its own sequence, counted by the
+// moves that color made, so draw offers and the like don't shift it.
 pub struct OpeningMove {
-    moves: Vec<ChessMove>,
+    white: Vec<ChessMove>,
+    black: Vec<ChessMove>,
+    blocked: Blocked,
 }
 
 impl OpeningMove {
     pub fn two_g_pawn_moves() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::G2, Square::G3, None),
-                ChessMove::new(Square::G7, Square::G6, None),
                 ChessMove::new(Square::G3, Square::G4, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::G7, Square::G6, None),
                 ChessMove::new(Square::G6, Square::G5, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn knight_and_back() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::B1, Square::C3, None),
-                ChessMove::new(Square::B8, Square::C6, None),
                 ChessMove::new(Square::C3, Square::B1, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::B8, Square::C6, None),
                 ChessMove::new(Square::C6, Square::B8, None),
             ],
+            blocked: Blocked::AllowAny,
         }
     }
 
     pub fn g_and_f_pawn() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::D2, Square::D3, None),
-                ChessMove::new(Square::D7, Square::D6, None),
                 ChessMove::new(Square::F2, Square::F3, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::D7, Square::D6, None),
                 ChessMove::new(Square::F7, Square::F6, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn edge_pawns_two_squares() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::A2, Square::A4, None),
-                ChessMove::new(Square::A7, Square::A5, None),
                 ChessMove::new(Square::H2, Square::H4, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::A7, Square::A5, None),
                 ChessMove::new(Square::H7, Square::H5, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn the_cheese_opening() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::C2, Square::C4, None),
-                ChessMove::new(Square::C7, Square::C5, None),
                 ChessMove::new(Square::D2, Square::D3, None),
-                ChessMove::new(Square::D7, Square::D6, None),
                 ChessMove::new(Square::E2, Square::E4, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::C7, Square::C5, None),
+                ChessMove::new(Square::D7, Square::D6, None),
                 ChessMove::new(Square::E7, Square::E5, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn rush_a() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::A2, Square::A4, None),
-                ChessMove::new(Square::A7, Square::A5, None),
                 ChessMove::new(Square::A4, Square::A5, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::A7, Square::A5, None),
                 ChessMove::new(Square::A5, Square::A4, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn rush_b() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::B2, Square::B4, None),
-                ChessMove::new(Square::B7, Square::B5, None),
                 ChessMove::new(Square::B4, Square::B5, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::B7, Square::B5, None),
                 ChessMove::new(Square::B5, Square::B4, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn scholars_mate() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::E2, Square::E4, None),
-                ChessMove::new(Square::E7, Square::E5, None),
                 ChessMove::new(Square::F1, Square::C4, None),
-                ChessMove::new(Square::F8, Square::C5, None),
                 ChessMove::new(Square::D1, Square::H5, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::E7, Square::E5, None),
+                ChessMove::new(Square::F8, Square::C5, None),
                 ChessMove::new(Square::D8, Square::H4, None),
             ],
+            blocked: Blocked::AllowAny,
         }
     }
 
     pub fn move_f_pawn_twice() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::F2, Square::F3, None),
-                ChessMove::new(Square::F7, Square::F6, None),
                 ChessMove::new(Square::F3, Square::F4, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::F7, Square::F6, None),
                 ChessMove::new(Square::F6, Square::F5, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn bring_both_rooks_out() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::A2, Square::A4, None),
-                ChessMove::new(Square::A7, Square::A5, None),
                 ChessMove::new(Square::A1, Square::A3, None),
-                ChessMove::new(Square::A8, Square::A6, None),
                 ChessMove::new(Square::H2, Square::H4, None),
-                ChessMove::new(Square::H7, Square::H5, None),
                 ChessMove::new(Square::H1, Square::H3, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::A7, Square::A5, None),
+                ChessMove::new(Square::A8, Square::A6, None),
+                ChessMove::new(Square::H7, Square::H5, None),
                 ChessMove::new(Square::H8, Square::H6, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn allow_fools_mate() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::F2, Square::F3, None),
-                ChessMove::new(Square::F7, Square::F6, None),
                 ChessMove::new(Square::G2, Square::G4, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::F7, Square::F6, None),
                 ChessMove::new(Square::G7, Square::G5, None),
             ],
+            blocked: Blocked::Skip,
         }
     }
 
     pub fn bongcloud() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::E2, Square::E4, None),
-                ChessMove::new(Square::E7, Square::E5, None),
                 ChessMove::new(Square::E1, Square::E2, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::E7, Square::E5, None),
                 ChessMove::new(Square::E8, Square::E7, None),
             ],
+            blocked: Blocked::Forfeit,
         }
     }
 
     pub fn bongcloud_and_back() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::E2, Square::E4, None),
-                ChessMove::new(Square::E7, Square::E5, None),
                 ChessMove::new(Square::E1, Square::E2, None),
-                ChessMove::new(Square::E8, Square::E7, None),
                 ChessMove::new(Square::E2, Square::E1, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::E7, Square::E5, None),
+                ChessMove::new(Square::E8, Square::E7, None),
                 ChessMove::new(Square::E7, Square::E8, None),
             ],
+            blocked: Blocked::Forfeit,
         }
     }
 
     pub fn knights_to_the_edges() -> Self {
         Self {
-            moves: vec![
+            white: vec![
                 ChessMove::new(Square::B1, Square::A3, None),
-                ChessMove::new(Square::B8, Square::A6, None),
                 ChessMove::new(Square::G1, Square::H3, None),
+            ],
+            black: vec![
+                ChessMove::new(Square::B8, Square::A6, None),
                 ChessMove::new(Square::G8, Square::H6, None),
             ],
+            blocked: Blocked::AllowAny,
+        }
+    }
+}
+
+impl OpeningMove {
+    // The move the side to move has to play, None if it may play anything.
+    fn forced_move(&self, context: &FilterContext) -> Option<ChessMove> {
+        let board = &context.board;
+        let steps = match context.side {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        };
+        let played = context.own_moves as usize;
+        match self.blocked {
+            Blocked::Forfeit => steps.get(played).copied(),
+            Blocked::AllowAny => steps.get(played).copied().filter(|step| board.legal(*step)),
+            Blocked::Skip => {
+                // Every move played was the first step after the previous one that could be
+                // played, a move that isn't part of the sequence means nothing could be played.
+                let own_moves = context.history.moves_by(context.side);
+                let next = own_moves.fold(0, |next, (_, chess_move)| {
+                    match steps.iter().skip(next).position(|step| step == chess_move) {
+                        Some(index) => next + index + 1,
+                        None => steps.len(),
+                    }
+                });
+                steps
+                    .iter()
+                    .skip(next)
+                    .find(|step| board.legal(**step))
+                    .copied()
+            }
         }
     }
 }
 
 impl MoveFilter for OpeningMove {
-    fn filter(&self, game: &Game, chess_move: &ChessMove) -> bool {
-        let turn = game.actions().len();
-        match self.moves.get(turn) {
-            Some(x) => !(x == chess_move),
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        match self.forced_move(context) {
+            Some(x) => x != *chess_move,
             None => false,
         }
     }
+
+    // The forced move is the same for every move, so it is only worked out once.
+    fn filter_moves(&self, context: &FilterContext) -> Vec<ChessMove> {
+        match self.forced_move(context) {
+            Some(forced) => context.legal_moves().filter(|m| *m == forced).collect(),
+            None => context.legal_moves().collect(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chess::Square;
+    use chess::{Game, Square};
 
     #[test]
     fn force_bong_cloud() {
         let mut game = Game::new();
         let bongcloud = OpeningMove::bongcloud();
-        assert_eq!(1, bongcloud.filter_moves(&game).len());
+        assert_eq!(
+            1,
+            bongcloud
+                .filter_moves(&FilterContext::from_game(&game))
+                .len()
+        );
+        game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+        assert_eq!(
+            1,
+            bongcloud
+                .filter_moves(&FilterContext::from_game(&game))
+                .len()
+        );
+    }
+
+    // 1. a4 a5 blocks the a-pawn of both players.
+    fn rush_a_blocked(blocked: Blocked) -> usize {
+        let mut game = Game::new();
+        game.make_move(ChessMove::new(Square::A2, Square::A4, None));
+        game.make_move(ChessMove::new(Square::A7, Square::A5, None));
+        let rule = OpeningMove {
+            blocked,
+            ..OpeningMove::rush_a()
+        };
+        rule.filter_moves(&FilterContext::from_game(&game)).len()
+    }
+
+    #[test]
+    fn blocked_step_policies() {
+        assert_eq!(rush_a_blocked(Blocked::Forfeit), 0);
+        assert_eq!(rush_a_blocked(Blocked::AllowAny), 20);
+        assert_eq!(rush_a_blocked(Blocked::Skip), 20);
+    }
+
+    #[test]
+    fn skip_continues_with_the_next_playable_step() {
+        let mut game = Game::new();
+        game.make_move(ChessMove::new(Square::A2, Square::A4, None));
+        game.make_move(ChessMove::new(Square::A7, Square::A5, None));
+        let rule = OpeningMove {
+            white: vec![
+                ChessMove::new(Square::A2, Square::A4, None),
+                ChessMove::new(Square::A4, Square::A5, None),
+                ChessMove::new(Square::H2, Square::H3, None),
+            ],
+            black: vec![],
+            blocked: Blocked::Skip,
+        };
+        let moves = rule.filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(moves, vec![ChessMove::new(Square::H2, Square::H3, None)]);
+        game.make_move(ChessMove::new(Square::H2, Square::H3, None));
+        game.make_move(ChessMove::new(Square::H7, Square::H6, None));
+        assert_eq!(
+            rule.filter_moves(&FilterContext::from_game(&game)).len(),
+            19
+        );
+    }
+
+    #[test]
+    fn draw_offer_doesnt_shift_the_sequence() {
+        let mut game = Game::new();
         game.make_move(ChessMove::new(Square::E2, Square::E4, None));
-        assert_eq!(1, bongcloud.filter_moves(&game).len());
+        game.offer_draw(Color::White);
+        let moves = OpeningMove::bongcloud().filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(moves, vec![ChessMove::new(Square::E7, Square::E5, None)]);
     }
 }
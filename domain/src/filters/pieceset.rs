@@ -0,0 +1,38 @@
+use chess::{Piece, ALL_PIECES};
+
+// A set of kinds of pieces as bits, so checking a piece doesn't search a list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PieceSet(u8);
+
+impl PieceSet {
+    pub const ALL: PieceSet = PieceSet::of(&ALL_PIECES);
+
+    pub const fn of(pieces: &[Piece]) -> Self {
+        let mut bits = 0;
+        let mut index = 0;
+        while index < pieces.len() {
+            bits |= 1 << pieces[index] as u8;
+            index += 1;
+        }
+        Self(bits)
+    }
+
+    pub fn contains(self, piece: Piece) -> bool {
+        self.0 & (1 << piece.to_index()) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_only_its_pieces() {
+        let minor = PieceSet::of(&[Piece::Knight, Piece::Bishop]);
+        assert!(minor.contains(Piece::Knight) && minor.contains(Piece::Bishop));
+        assert!(!minor.contains(Piece::Pawn) && !minor.contains(Piece::King));
+        assert!(ALL_PIECES
+            .iter()
+            .all(|piece| PieceSet::ALL.contains(*piece)));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::filters::context::FilterContext;
+use crate::filters::history::is_capture;
+use crate::filters::material::value;
+use crate::filters::movesetfilter::MoveSetFilter;
+use chess::{Board, ChessMove, Piece, EMPTY};
+
+// Only the moves with the lowest priority are allowed.
+// A rule like this never takes away the last legal move, it only decides which one you play.
+pub struct Priority {
+    priority: fn(&Board, &ChessMove) -> u8,
+}
+
+impl Priority {
+    pub fn compulsory_capture() -> Self {
+        Self {
+            priority: |board, chess_move| !is_capture(board, chess_move) as u8,
+        }
+    }
+
+    pub fn least_valuable_piece() -> Self {
+        Self {
+            priority: |board, chess_move| match board.piece_on(chess_move.get_source()) {
+                Some(Piece::King) | None => u8::MAX,
+                Some(piece) => value(piece),
+            },
+        }
+    }
+
+    pub fn compulsory_check() -> Self {
+        Self {
+            priority: |board, chess_move| {
+                (board.make_move_new(*chess_move).checkers() == &EMPTY) as u8
+            },
+        }
+    }
+}
+
+impl MoveSetFilter for Priority {
+    fn filter_set(&self, context: &FilterContext, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+        let board = context.board;
+        let Some(lowest) = moves.iter().map(|m| (self.priority)(&board, m)).min() else {
+            return moves;
+        };
+        moves
+            .into_iter()
+            .filter(|m| (self.priority)(&board, m) == lowest)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Game, Square};
+    use std::str::FromStr;
+
+    #[test]
+    // After 1. e4 d5 white has to take on d5, with the pawn or (later) the queen.
+    fn must_capture() {
+        let mut game = Game::new();
+        game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+        game.make_move(ChessMove::new(Square::D7, Square::D5, None));
+        let moves = Priority::compulsory_capture().filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(moves, vec![ChessMove::new(Square::E4, Square::D5, None)]);
+    }
+
+    #[test]
+    fn en_passant_is_a_capture() {
+        let game = Game::from_str("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").unwrap();
+        let moves = Priority::compulsory_capture().filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(moves, vec![ChessMove::new(Square::D5, Square::E6, None)]);
+    }
+
+    #[test]
+    // In check only the moves that get out of check count, capturing the checker is forced.
+    fn capture_out_of_check() {
+        let game = Game::from_str("4k3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap();
+        let moves = Priority::compulsory_capture().filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(moves, vec![ChessMove::new(Square::E1, Square::D2, None)]);
+        // The queen is defended here, so the king has to walk away instead.
+        let game = Game::from_str("3rk3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap();
+        let moves = Priority::compulsory_capture().filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(moves, vec![ChessMove::new(Square::E1, Square::F1, None)]);
+    }
+
+    #[test]
+    fn pawns_move_first() {
+        let moves =
+            Priority::least_valuable_piece().filter_moves(&FilterContext::from_game(&Game::new()));
+        assert_eq!(moves.len(), 16);
+        // Only the king can move.
+        let game = Game::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            Priority::least_valuable_piece()
+                .filter_moves(&FilterContext::from_game(&game))
+                .len(),
+            5
+        );
+    }
+
+    #[test]
+    fn must_check() {
+        let game = Game::from_str("7k/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert_eq!(
+            Priority::compulsory_check()
+                .filter_moves(&FilterContext::from_game(&game))
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn stalemate_stays_stalemate() {
+        let game = Game::from_str("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(Priority::compulsory_capture()
+            .filter_moves(&FilterContext::from_game(&game))
+            .is_empty());
+    }
+}
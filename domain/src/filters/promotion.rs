@@ -0,0 +1,91 @@
+use crate::filters::context::FilterContext;
+use crate::filters::movefilter::MoveFilter;
+use chess::{Board, ChessMove, Piece};
+
+// Only looks at promotions, the predicate gets the position and the piece promoted to.
+pub struct Promotion {
+    forbidden: fn(&Board, Piece) -> bool,
+}
+
+impl Promotion {
+    pub fn only_underpromote() -> Self {
+        Self {
+            forbidden: |_, piece| piece == Piece::Queen,
+        }
+    }
+
+    pub fn cant_promote() -> Self {
+        Self {
+            forbidden: |_, _| true,
+        }
+    }
+
+    pub fn only_promote_to_lost_pieces() -> Self {
+        Self {
+            forbidden: |board, piece| {
+                let on_board =
+                    (board.pieces(piece) & board.color_combined(board.side_to_move())).popcnt();
+                on_board >= starting_count(piece)
+            },
+        }
+    }
+}
+
+impl MoveFilter for Promotion {
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        match chess_move.get_promotion() {
+            Some(piece) => (self.forbidden)(&context.board, piece),
+            None => false,
+        }
+    }
+}
+
+fn starting_count(piece: Piece) -> u32 {
+    match piece {
+        Piece::Pawn => 8,
+        Piece::Knight | Piece::Bishop | Piece::Rook => 2,
+        Piece::Queen | Piece::King => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Game, Square};
+    use std::str::FromStr;
+
+    fn promotions(filter: Promotion, fen: &str) -> Vec<Piece> {
+        let game = Game::from_str(fen).unwrap();
+        filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .iter()
+            .filter_map(|m| m.get_promotion())
+            .collect()
+    }
+
+    #[test]
+    fn underpromotion_only() {
+        let pieces = promotions(
+            Promotion::only_underpromote(),
+            "4k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        );
+        assert_eq!(pieces, vec![Piece::Knight, Piece::Rook, Piece::Bishop]);
+    }
+
+    #[test]
+    fn no_promotion() {
+        let game = Game::from_str("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let moves = Promotion::cant_promote().filter_moves(&FilterContext::from_game(&game));
+        assert!(moves.iter().all(|m| m.get_source() != Square::A7));
+    }
+
+    #[test]
+    // White still has its queen, both rooks and one bishop.
+    fn promote_to_lost_piece() {
+        let pieces = promotions(
+            Promotion::only_promote_to_lost_pieces(),
+            "4k3/P7/8/8/8/8/8/R2QKB1R w - - 0 1",
+        );
+        assert_eq!(pieces, vec![Piece::Knight, Piece::Bishop]);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::filters::context::FilterContext;
+use crate::filters::material::value;
+use crate::filters::movefilter::MoveFilter;
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, BitBoard,
+    Board, ChessMove, Color, MoveGen, Piece, Rank, Square, EMPTY,
+};
+
+// Rules that look at the position after the move instead of only its source and destination.
+// The predicate gets the position before and after the move and returns true if it is forbidden.
+// The player who moved is the side to move on the first board.
+pub struct ResultingPosition {
+    forbidden: fn(&Board, &Board) -> bool,
+}
+
+impl ResultingPosition {
+    pub fn queen_cant_give_check() -> Self {
+        Self {
+            forbidden: queen_gives_check,
+        }
+    }
+
+    pub fn king_stays_on_first_two_ranks() -> Self {
+        Self {
+            forbidden: king_left_first_two_ranks,
+        }
+    }
+
+    pub fn keep_the_bishop_pair() -> Self {
+        Self {
+            forbidden: bishop_pair_can_be_captured,
+        }
+    }
+
+    pub fn no_piece_en_prise() -> Self {
+        Self {
+            forbidden: piece_left_en_prise,
+        }
+    }
+}
+
+impl MoveFilter for ResultingPosition {
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        let board = &context.board;
+        (self.forbidden)(board, &board.make_move_new(*chess_move))
+    }
+}
+
+pub fn queen_gives_check(before: &Board, after: &Board) -> bool {
+    let queens = after.pieces(Piece::Queen) & after.color_combined(before.side_to_move());
+    after.checkers() & queens != EMPTY
+}
+
+pub fn king_left_first_two_ranks(before: &Board, after: &Board) -> bool {
+    let mover = before.side_to_move();
+    let rank = after.king_square(mover).get_rank();
+    match mover {
+        Color::White => rank != Rank::First && rank != Rank::Second,
+        Color::Black => rank != Rank::Eighth && rank != Rank::Seventh,
+    }
+}
+
+// Only applies while the player still has a bishop on both square colours.
+pub fn bishop_pair_can_be_captured(before: &Board, after: &Board) -> bool {
+    let mover = before.side_to_move();
+    let bishops = after.pieces(Piece::Bishop) & after.color_combined(mover);
+    let light = bishops.into_iter().any(is_light_square);
+    let dark = bishops.into_iter().any(|square| !is_light_square(square));
+    light && dark && can_be_captured(after, bishops)
+}
+
+// A piece is en prise when the opponent can take it and it's either undefended or
+// the attacker is worth less than the piece.
+pub fn piece_left_en_prise(before: &Board, after: &Board) -> bool {
+    let mover = before.side_to_move();
+    let own_pieces = after.color_combined(mover) & !after.pieces(Piece::King);
+    let mut captures = MoveGen::new_legal(after);
+    captures.set_iterator_mask(own_pieces);
+    captures.any(|capture| {
+        let target = capture.get_dest();
+        let attacker = after.piece_on(capture.get_source()).unwrap();
+        let victim = after.piece_on(target).unwrap();
+        attackers(after, target, mover) == EMPTY || value(attacker) < value(victim)
+    })
+}
+
+fn can_be_captured(after: &Board, pieces: BitBoard) -> bool {
+    let mut captures = MoveGen::new_legal(after);
+    captures.set_iterator_mask(pieces);
+    captures.next().is_some()
+}
+
+// Pieces of this color that attack the square, ignoring pins.
+fn attackers(board: &Board, square: Square, color: Color) -> BitBoard {
+    let blockers = *board.combined();
+    let own = *board.color_combined(color);
+    let diagonal = board.pieces(Piece::Bishop) | board.pieces(Piece::Queen);
+    let straight = board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    own & ((get_pawn_attacks(square, !color, *board.pieces(Piece::Pawn)))
+        | (get_knight_moves(square) & board.pieces(Piece::Knight))
+        | (get_bishop_moves(square, blockers) & diagonal)
+        | (get_rook_moves(square, blockers) & straight)
+        | (get_king_moves(square) & board.pieces(Piece::King)))
+}
+
+fn is_light_square(square: Square) -> bool {
+    (square.get_rank().to_index() + square.get_file().to_index()) % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Game;
+    use std::str::FromStr;
+
+    #[test]
+    // White queen on d1 can check the king on h8 from a1, d4, d8 and h5.
+    fn queen_cant_give_check() {
+        let game = Game::from_str("7k/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let all_moves = MoveGen::new_legal(&game.current_position()).len();
+        let filter = ResultingPosition::queen_cant_give_check();
+        assert_eq!(
+            all_moves - 4,
+            filter.filter_moves(&FilterContext::from_game(&game)).len()
+        );
+    }
+
+    #[test]
+    fn king_stays_home() {
+        let game = Game::from_str("4k3/8/8/8/8/8/4K3/8 w - - 0 1").unwrap();
+        let filter = ResultingPosition::king_stays_on_first_two_ranks();
+        // Ke2 can go to d1, e1, f1, d2 and f2 but not to the third rank.
+        assert_eq!(
+            5,
+            filter.filter_moves(&FilterContext::from_game(&game)).len()
+        );
+    }
+
+    #[test]
+    // White's Bc4 is attacked by the d5 pawn, so it has to move or the pawn has to go.
+    fn bishop_pair_stays_safe() {
+        let game = Game::from_str("rnbqkbnr/ppp1pppp/8/3p4/2B1P3/8/PPPP1PPP/RNBQK1NR w KQkq - 0 3")
+            .unwrap();
+        let filter = ResultingPosition::keep_the_bishop_pair();
+        let moves = filter.filter_moves(&FilterContext::from_game(&game));
+        assert!(moves.contains(&ChessMove::new(Square::E4, Square::D5, None)));
+        assert!(moves.contains(&ChessMove::new(Square::C4, Square::B3, None)));
+        assert!(!moves.contains(&ChessMove::new(Square::G1, Square::F3, None)));
+    }
+
+    #[test]
+    fn cant_hang_a_piece() {
+        // Knight on e4 attacked by the pawn on d5: it has to move somewhere safe.
+        let game = Game::from_str("4k3/8/8/3p4/4N3/8/8/4K3 w - - 0 1").unwrap();
+        let filter = ResultingPosition::no_piece_en_prise();
+        let moves = filter.filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::E2, None)));
+        assert!(moves.contains(&ChessMove::new(Square::E4, Square::F6, None)));
+        assert!(moves.contains(&ChessMove::new(Square::E4, Square::C3, None)));
+        // Defended pieces attacked by an equal piece are fine.
+        let game = Game::from_str("4k3/8/8/3n4/8/4N3/8/4K3 w - - 0 1").unwrap();
+        assert!(filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .contains(&ChessMove::new(Square::E1, Square::F2, None)));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::filters::context::FilterContext;
+use crate::filters::history::is_capture;
+use crate::filters::material::material;
+use crate::filters::movefilter::MoveFilter;
+use chess::ChessMove;
+
+// When a scheduled rule is active. Outside of its phase the player plays normal chess.
+pub enum Phase {
+    // From the first to the last turn, both included.
+    Turns(u16, u16),
+    WhileAhead,
+    UntilFirstCapture,
+}
+
+impl Phase {
+    fn is_active(&self, context: &FilterContext) -> bool {
+        match self {
+            Phase::Turns(first, last) => (*first..=*last).contains(&context.turn),
+            Phase::WhileAhead => {
+                material(&context.board, context.side) > material(&context.board, !context.side)
+            }
+            Phase::UntilFirstCapture => !context
+                .history
+                .moves()
+                .any(|(board, chess_move)| is_capture(board, chess_move)),
+        }
+    }
+}
+
+// Wraps any rule so it only applies during part of the game.
+pub struct Scheduled<F: MoveFilter> {
+    filter: F,
+    phase: Phase,
+}
+
+impl<F: MoveFilter> Scheduled<F> {
+    pub fn new(filter: F, phase: Phase) -> Self {
+        Self { filter, phase }
+    }
+}
+
+impl<F: MoveFilter> MoveFilter for Scheduled<F> {
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        self.phase.is_active(context) && self.filter.filter(context, chess_move)
+    }
+
+    // The phase is the same for every move, so it is only checked once.
+    fn filter_moves(&self, context: &FilterContext) -> Vec<ChessMove> {
+        if self.phase.is_active(context) {
+            self.filter.filter_moves(context)
+        } else {
+            context.legal_moves().collect()
+        }
+    }
+}
+
+// Plays by one rule until the given turn and by the other from then on.
+pub struct Swap<A: MoveFilter, B: MoveFilter> {
+    before: A,
+    after: B,
+    turn: u16,
+}
+
+impl<A: MoveFilter, B: MoveFilter> Swap<A, B> {
+    pub fn new(before: A, after: B, turn: u16) -> Self {
+        Self {
+            before,
+            after,
+            turn,
+        }
+    }
+}
+
+impl<A: MoveFilter, B: MoveFilter> MoveFilter for Swap<A, B> {
+    fn filter(&self, context: &FilterContext, chess_move: &ChessMove) -> bool {
+        if context.turn < self.turn {
+            self.before.filter(context, chess_move)
+        } else {
+            self.after.filter(context, chess_move)
+        }
+    }
+
+    fn filter_moves(&self, context: &FilterContext) -> Vec<ChessMove> {
+        if context.turn < self.turn {
+            self.before.filter_moves(context)
+        } else {
+            self.after.filter_moves(context)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::cantcapture::CantCapture;
+    use crate::filters::moveto::MoveTo;
+    use chess::{Game, Square};
+    use std::str::FromStr;
+
+    fn play(moves: &[(Square, Square)]) -> Game {
+        let mut game = Game::new();
+        for (source, dest) in moves {
+            game.make_move(ChessMove::new(*source, *dest, None));
+        }
+        game
+    }
+
+    #[test]
+    fn only_active_during_turns() {
+        let filter = Scheduled::new(MoveTo::cant_play_on_the_e_file(), Phase::Turns(2, 3));
+        assert_eq!(
+            filter
+                .filter_moves(&FilterContext::from_game(&Game::new()))
+                .len(),
+            20
+        );
+        let game = play(&[(Square::D2, Square::D4), (Square::D7, Square::D5)]);
+        assert!(!filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .contains(&ChessMove::new(Square::E2, Square::E4, None)));
+    }
+
+    #[test]
+    fn only_active_while_ahead() {
+        // White is a knight up and can't capture with the queen, in the second position black is
+        // ahead.
+        let filter = Scheduled::new(
+            CantCapture::queen_cant_capture_anything(),
+            Phase::WhileAhead,
+        );
+        let game = Game::from_str("4k3/8/8/8/8/8/2p5/3QKN2 w - - 0 1").unwrap();
+        let capture = ChessMove::new(Square::D1, Square::C2, None);
+        assert!(!filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .contains(&capture));
+        let game = Game::from_str("3qk3/8/8/8/8/8/2p5/3QK3 w - - 0 1").unwrap();
+        assert!(filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .contains(&capture));
+    }
+
+    #[test]
+    fn lifted_after_first_capture() {
+        let filter = Scheduled::new(MoveTo::cant_play_on_the_e_file(), Phase::UntilFirstCapture);
+        let game = play(&[(Square::E2, Square::E4), (Square::D7, Square::D5)]);
+        let e_file_move = ChessMove::new(Square::E4, Square::E5, None);
+        assert!(!filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .contains(&e_file_move));
+        let game = play(&[
+            (Square::E2, Square::E4),
+            (Square::D7, Square::D5),
+            (Square::B1, Square::C3),
+            (Square::D5, Square::E4),
+        ]);
+        assert!(filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .contains(&ChessMove::new(Square::C3, Square::E4, None)));
+    }
+
+    #[test]
+    fn swaps_at_turn() {
+        let filter = Swap::new(
+            MoveTo::cant_play_on_the_e_file(),
+            MoveTo::cant_play_on_the_c_file(),
+            2,
+        );
+        let e4 = ChessMove::new(Square::E2, Square::E4, None);
+        let c4 = ChessMove::new(Square::C2, Square::C4, None);
+        let moves = filter.filter_moves(&FilterContext::from_game(&Game::new()));
+        assert!(!moves.contains(&e4) && moves.contains(&c4));
+        let game = play(&[(Square::D2, Square::D4), (Square::D7, Square::D5)]);
+        let moves = filter.filter_moves(&FilterContext::from_game(&game));
+        assert!(moves.contains(&e4) && !moves.contains(&c4));
+    }
+}
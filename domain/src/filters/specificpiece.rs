@@ -0,0 +1,164 @@
+use crate::filters::context::FilterContext;
+use crate::filters::history::is_capture;
+use crate::filters::identity::{castling_rook, starting_square, PieceTracker};
+use crate::filters::movesetfilter::MoveSetFilter;
+use chess::{Board, ChessMove, Piece, Square};
+
+enum Restriction {
+    NeverMoves,
+    CantCapture,
+    // Once the piece has been captured without promoting, you have no moves left.
+    MustPromote,
+}
+
+// Rules about one particular piece, identified by the square it started on (from white's side).
+// Games that don't start with that piece on its square don't have it, so the rule doesn't apply.
+pub struct SpecificPiece {
+    origin: Square,
+    piece: Piece,
+    restriction: Restriction,
+}
+
+impl SpecificPiece {
+    pub fn a_rook_never_moves() -> Self {
+        Self {
+            origin: Square::A1,
+            piece: Piece::Rook,
+            restriction: Restriction::NeverMoves,
+        }
+    }
+
+    pub fn kings_knight_cant_capture() -> Self {
+        Self {
+            origin: Square::G1,
+            piece: Piece::Knight,
+            restriction: Restriction::CantCapture,
+        }
+    }
+
+    pub fn e_pawn_must_promote() -> Self {
+        Self {
+            origin: Square::E2,
+            piece: Piece::Pawn,
+            restriction: Restriction::MustPromote,
+        }
+    }
+
+    fn forbidden(
+        &self,
+        board: &Board,
+        tracker: &PieceTracker,
+        origin: Square,
+        chess_move: &ChessMove,
+    ) -> bool {
+        let moves_piece = tracker.origin(chess_move.get_source()) == Some(origin)
+            || castling_rook(board, chess_move)
+                .is_some_and(|(rook, _)| tracker.origin(rook) == Some(origin));
+        match self.restriction {
+            Restriction::NeverMoves => moves_piece,
+            Restriction::CantCapture => moves_piece && is_capture(board, chess_move),
+            Restriction::MustPromote => {
+                !tracker.has_promoted(origin) && tracker.square_of(origin).is_none()
+            }
+        }
+    }
+}
+
+impl MoveSetFilter for SpecificPiece {
+    fn filter_set(&self, context: &FilterContext, moves: Vec<ChessMove>) -> Vec<ChessMove> {
+        let origin = starting_square(self.origin, context.side);
+        let start = context.history.start();
+        if start.piece_on(origin) != Some(self.piece)
+            || start.color_on(origin) != Some(context.side)
+        {
+            return moves;
+        }
+        let tracker = PieceTracker::new(&context.history);
+        moves
+            .into_iter()
+            .filter(|chess_move| !self.forbidden(&context.board, &tracker, origin, chess_move))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Game;
+    use std::str::FromStr;
+
+    fn play(moves: &[(Square, Square)]) -> Game {
+        let mut game = Game::new();
+        for (source, dest) in moves {
+            game.make_move(ChessMove::new(*source, *dest, None));
+        }
+        game
+    }
+
+    #[test]
+    // The a-rook can't castle either, but the king can still move.
+    fn a_rook_stays_home() {
+        let game = play(&[
+            (Square::D2, Square::D4),
+            (Square::D7, Square::D5),
+            (Square::B1, Square::C3),
+            (Square::B8, Square::C6),
+            (Square::C1, Square::F4),
+            (Square::C8, Square::F5),
+            (Square::D1, Square::D2),
+            (Square::D8, Square::D7),
+        ]);
+        let moves =
+            SpecificPiece::a_rook_never_moves().filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::E1, Square::C1, None)));
+        assert!(!moves.contains(&ChessMove::new(Square::A1, Square::B1, None)));
+        assert!(moves.contains(&ChessMove::new(Square::E1, Square::D1, None)));
+    }
+
+    #[test]
+    fn only_the_kings_knight_cant_capture() {
+        let game = play(&[
+            (Square::G1, Square::F3),
+            (Square::D7, Square::D5),
+            (Square::B1, Square::C3),
+            (Square::E7, Square::E5),
+        ]);
+        let moves = SpecificPiece::kings_knight_cant_capture()
+            .filter_moves(&FilterContext::from_game(&game));
+        assert!(!moves.contains(&ChessMove::new(Square::F3, Square::E5, None)));
+        assert!(moves.contains(&ChessMove::new(Square::C3, Square::D5, None)));
+    }
+
+    #[test]
+    fn rule_needs_the_piece_on_its_square_at_the_start() {
+        // Started after 1.e4 e5, there is no e-pawn on e2 to promote.
+        let game =
+            Game::from_str("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+        let moves =
+            SpecificPiece::e_pawn_must_promote().filter_moves(&FilterContext::from_game(&game));
+        assert_eq!(moves.len(), 29);
+        // The knight on a1 isn't the a-rook.
+        let game = Game::from_str("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+        let moves =
+            SpecificPiece::a_rook_never_moves().filter_moves(&FilterContext::from_game(&game));
+        assert!(moves.contains(&ChessMove::new(Square::A1, Square::B3, None)));
+    }
+
+    #[test]
+    fn lose_when_e_pawn_is_captured() {
+        let game = play(&[(Square::E2, Square::E4), (Square::D7, Square::D5)]);
+        let filter = SpecificPiece::e_pawn_must_promote();
+        assert!(!filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .is_empty());
+        let game = play(&[
+            (Square::E2, Square::E4),
+            (Square::D7, Square::D5),
+            (Square::B1, Square::C3),
+            (Square::D5, Square::E4),
+        ]);
+        assert!(filter
+            .filter_moves(&FilterContext::from_game(&game))
+            .is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use chess::{Action, Board, BoardStatus, ChessMove, Color, Error, GameResult, Piece};
+use std::str::FromStr;
+
+// Works like a chess::Game, which replays every action whenever the position is asked for.
+// This one keeps the current position, the moves with the position they were played in and the
+// hashes for repetitions up to date on every action, so asking for them doesn't depend on the
+// length of the game.
+#[derive(Clone, Debug)]
+pub struct GameState {
+    start: Board,
+    board: Board,
+    actions: Vec<Action>,
+    moves: Vec<(Board, ChessMove)>,
+    // Zobrist hashes of the positions since the last capture, pawn move or loss of castling
+    // rights, the current one included. Earlier positions can't come back.
+    hashes: Vec<u64>,
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        Self::new_with_board(Board::default())
+    }
+
+    pub fn new_with_board(board: Board) -> Self {
+        Self {
+            start: board,
+            board,
+            actions: Vec::new(),
+            moves: Vec::new(),
+            hashes: vec![board.get_hash()],
+        }
+    }
+
+    pub fn start_position(&self) -> Board {
+        self.start
+    }
+
+    pub fn current_position(&self) -> Board {
+        self.board
+    }
+
+    pub fn actions(&self) -> &Vec<Action> {
+        &self.actions
+    }
+
+    // The moves played so far, with the position before each of them.
+    pub fn moves(&self) -> &[(Board, ChessMove)] {
+        &self.moves
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.board.side_to_move()
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        match self.board.status() {
+            BoardStatus::Checkmate => match self.side_to_move() {
+                Color::White => Some(GameResult::BlackCheckmates),
+                Color::Black => Some(GameResult::WhiteCheckmates),
+            },
+            BoardStatus::Stalemate => Some(GameResult::Stalemate),
+            BoardStatus::Ongoing => match self.actions.last() {
+                Some(Action::AcceptDraw) => Some(GameResult::DrawAccepted),
+                Some(Action::DeclareDraw) => Some(GameResult::DrawDeclared),
+                Some(Action::Resign(Color::White)) => Some(GameResult::WhiteResigns),
+                Some(Action::Resign(Color::Black)) => Some(GameResult::BlackResigns),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn make_move(&mut self, chess_move: ChessMove) -> bool {
+        if self.result().is_some() || !self.board.legal(chess_move) {
+            return false;
+        }
+        let before = self.board;
+        self.board = before.make_move_new(chess_move);
+        let irreversible = before.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            || before.piece_on(chess_move.get_dest()).is_some()
+            || before.castle_rights(Color::White) != self.board.castle_rights(Color::White)
+            || before.castle_rights(Color::Black) != self.board.castle_rights(Color::Black);
+        if irreversible {
+            self.hashes.clear();
+        }
+        self.hashes.push(self.board.get_hash());
+        self.moves.push((before, chess_move));
+        self.actions.push(Action::MakeMove(chess_move));
+        true
+    }
+
+    // Threefold repetition or fifty moves by both players without a capture or pawn move.
+    pub fn can_declare_draw(&self) -> bool {
+        if self.result().is_some() {
+            return false;
+        }
+        let current = self.board.get_hash();
+        self.hashes.len() > 100 || self.hashes.iter().filter(|&&hash| hash == current).count() >= 3
+    }
+
+    pub fn declare_draw(&mut self) -> bool {
+        self.push_if(self.can_declare_draw(), Action::DeclareDraw)
+    }
+
+    pub fn offer_draw(&mut self, color: Color) -> bool {
+        self.push_if(true, Action::OfferDraw(color))
+    }
+
+    // The offer has to be the last action, or the one before the opponent's last move.
+    pub fn accept_draw(&mut self) -> bool {
+        let offered = match self.actions.as_slice() {
+            [.., Action::OfferDraw(_)] => true,
+            [.., Action::OfferDraw(color), _] => *color == !self.side_to_move(),
+            _ => false,
+        };
+        self.push_if(offered, Action::AcceptDraw)
+    }
+
+    pub fn resign(&mut self, color: Color) -> bool {
+        self.push_if(true, Action::Resign(color))
+    }
+
+    fn push_if(&mut self, allowed: bool, action: Action) -> bool {
+        if !allowed || self.result().is_some() {
+            return false;
+        }
+        self.actions.push(action);
+        true
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for GameState {
+    type Err = Error;
+
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new_with_board(Board::from_str(fen)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Game, MoveGen, Square};
+
+    #[test]
+    fn same_as_a_replayed_game() {
+        let mut state = GameState::new();
+        let mut game = Game::new();
+        for ply in 0..120 {
+            let moves: Vec<ChessMove> = MoveGen::new_legal(&state.current_position()).collect();
+            if moves.is_empty() {
+                break;
+            }
+            let chess_move = moves[(ply * 7) % moves.len()];
+            assert!(state.make_move(chess_move));
+            game.make_move(chess_move);
+            assert_eq!(state.current_position(), game.current_position());
+            assert_eq!(state.side_to_move(), game.side_to_move());
+            assert_eq!(state.result(), game.result());
+            assert_eq!(state.can_declare_draw(), game.can_declare_draw());
+        }
+        assert_eq!(state.actions(), game.actions());
+    }
+
+    #[test]
+    fn draw_by_repetition() {
+        let mut state = GameState::new();
+        for _ in 0..2 {
+            for (source, dest) in [
+                (Square::G1, Square::F3),
+                (Square::G8, Square::F6),
+                (Square::F3, Square::G1),
+                (Square::F6, Square::G8),
+            ] {
+                assert!(!state.can_declare_draw());
+                state.make_move(ChessMove::new(source, dest, None));
+            }
+        }
+        assert!(state.declare_draw());
+        assert_eq!(state.result(), Some(GameResult::DrawDeclared));
+        assert!(!state.make_move(ChessMove::new(Square::E2, Square::E4, None)));
+    }
+
+    #[test]
+    fn draw_accepted_after_a_move() {
+        let mut state = GameState::new();
+        state.offer_draw(Color::White);
+        state.make_move(ChessMove::new(Square::E2, Square::E4, None));
+        assert!(state.accept_draw());
+        assert_eq!(state.result(), Some(GameResult::DrawAccepted));
+    }
+}
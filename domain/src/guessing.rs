@@ -0,0 +1,54 @@
+// Side game: players can try to guess the opponent's Elo Stealo rule a limited number of times.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum GuessReward {
+    // A correct guess only scores points.
+    #[default]
+    Points,
+    // A correct guess reveals the opponent's rule and lifts it for the rest of the game.
+    RevealAndLift,
+}
+
+impl GuessReward {
+    pub fn from_id(id: i16) -> Self {
+        match id {
+            1 => GuessReward::RevealAndLift,
+            _ => GuessReward::Points,
+        }
+    }
+
+    pub fn id(self) -> i16 {
+        match self {
+            GuessReward::Points => 0,
+            GuessReward::RevealAndLift => 1,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GuessError {
+    GameOver,
+    NoGuessesLeft,
+    AlreadyRevealed,
+}
+
+pub const DEFAULT_MAX_GUESSES: u8 = 3;
+
+// A correct guess scores a point plus a point for every guess that was left unused.
+pub fn guess_points(guesses: &[i32], rule_id: i32, max_guesses: u8) -> u32 {
+    match guesses.iter().position(|&guess| guess == rule_id) {
+        Some(index) => (max_guesses as u32).saturating_sub(index as u32),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_guess_scores_most() {
+        assert_eq!(guess_points(&[37], 37, 3), 3);
+        assert_eq!(guess_points(&[12, 5, 37], 37, 3), 1);
+        assert_eq!(guess_points(&[12, 5], 37, 3), 0);
+    }
+}
@@ -1,12 +1,39 @@
+pub mod adaptive;
+pub mod analysis;
+pub mod bonus;
 pub mod chessgame;
-mod move_generator;
+pub mod clock;
+pub mod commitment;
+pub mod gamestate;
+pub mod guessing;
+pub mod move_generator;
+pub mod movecache;
+pub mod odds;
+pub mod opponent;
+pub mod pgn;
+pub mod restrictiveness;
 pub mod stringtomove;
+pub mod visibility;
 
-mod filters {
+pub mod filters {
+    pub mod budget;
     pub mod cantcapture;
+    pub mod castling;
+    pub mod context;
+    pub mod history;
+    pub mod identity;
+    pub mod material;
     pub mod moveafter;
     pub mod movefilter;
+    pub mod movesetfilter;
     pub mod moveto;
     pub mod nofilter;
+    pub mod obligation;
     pub mod openingmove;
+    pub mod pieceset;
+    pub mod priority;
+    pub mod promotion;
+    pub mod resultingposition;
+    pub mod schedule;
+    pub mod specificpiece;
 }
@@ -1,91 +1,218 @@
+use crate::filters::budget::{Budget, NotTwiceInARow};
 use crate::filters::cantcapture::CantCapture;
+use crate::filters::castling::Castling;
+use crate::filters::context::FilterContext;
 use crate::filters::moveafter::MoveAfter;
 use crate::filters::movefilter::MoveFilter;
+use crate::filters::movesetfilter::MoveSetFilter;
 use crate::filters::moveto::MoveTo;
 use crate::filters::nofilter::NoFilter;
+use crate::filters::obligation::{Obligation, Progress};
 use crate::filters::openingmove::OpeningMove;
-use chess::{ChessMove, Game};
+use crate::filters::priority::Priority;
+use crate::filters::promotion::Promotion;
+use crate::filters::resultingposition::ResultingPosition;
+use crate::filters::schedule::{Phase, Scheduled, Swap};
+use crate::filters::specificpiece::SpecificPiece;
+use chess::{ChessMove, Color};
+use std::sync::OnceLock;
+
+// Ids above this one are played as normal chess, keep it in sync with the mappings below.
+pub const HIGHEST_FILTER_ID: i32 = 87;
 
 // Select filter based on id and filter the moves. Default is normal chess.
+// The context is made once for the position, see FilterContext.
 // Range 1-21 are filters that limit captures.
 // Range 22-33 Prevents pieces from moving after move x
 // Range 34-45 limits movement of pieces to certain squares
 // Range 45-59 forces a number of opening moves
-pub fn generate_moves(filter_id: i32, game: &Game) -> Vec<ChessMove> {
+// Range 60-63 looks at the position after the move
+// Range 64-66 decides between all legal moves
+// Range 67-69 depends on the moves played earlier
+// Range 70-72 is about one specific piece
+// Range 73-74 limits castling
+// Range 75-77 limits promotion
+// Range 78-81 only applies during part of the game
+// Range 82-84 doesn't filter anything but grants a bonus move, see bonus.rs
+// Range 85-87 has to reach a number of goals by a deadline, in any order
+pub fn generate_moves(filter_id: i32, context: &FilterContext) -> Vec<ChessMove> {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    let rules = RULES.get_or_init(|| (0..=HIGHEST_FILTER_ID).map(rule).collect());
+    let rule = usize::try_from(filter_id)
+        .ok()
+        .and_then(|index| rules.get(index))
+        .unwrap_or(&rules[0]);
+    rule(context)
+}
+
+// Rules are built once and shared between all games, they don't keep state between calls.
+type Rule = Box<dyn Fn(&FilterContext) -> Vec<ChessMove> + Send + Sync>;
+
+fn filter(rule: impl MoveFilter + Send + Sync + 'static) -> Rule {
+    Box::new(move |context| rule.filter_moves(context))
+}
+
+fn set_filter(rule: impl MoveSetFilter + Send + Sync + 'static) -> Rule {
+    Box::new(move |context| rule.filter_moves(context))
+}
+
+fn rule(filter_id: i32) -> Rule {
+    if let Some(obligation) = obligation(filter_id) {
+        return set_filter(obligation);
+    }
+    if let Some(budget) = budget(filter_id) {
+        return set_filter(budget);
+    }
+    match filter_id {
+        81 => filter(Swap::new(
+            MoveTo::queen_can_only_move_to_dark_squares(),
+            MoveTo::queen_can_only_move_to_light_squares(),
+            15,
+        )),
+        80 => filter(Scheduled::new(
+            MoveTo::cant_play_on_the_e_file(),
+            Phase::UntilFirstCapture,
+        )),
+        79 => filter(Scheduled::new(
+            CantCapture::queen_cant_capture_anything(),
+            Phase::WhileAhead,
+        )),
+        78 => filter(Scheduled::new(
+            CantCapture::queen_cant_capture_anything(),
+            Phase::Turns(1, 10),
+        )),
+        77 => filter(Promotion::only_promote_to_lost_pieces()),
+        76 => filter(Promotion::cant_promote()),
+        75 => filter(Promotion::only_underpromote()),
+        74 => filter(Castling::must_castle_queenside()),
+        73 => filter(Castling::never_castle()),
+        72 => set_filter(SpecificPiece::e_pawn_must_promote()),
+        71 => set_filter(SpecificPiece::kings_knight_cant_capture()),
+        70 => set_filter(SpecificPiece::a_rook_never_moves()),
+        69 => set_filter(NotTwiceInARow::new()),
+        66 => set_filter(Priority::compulsory_check()),
+        65 => set_filter(Priority::least_valuable_piece()),
+        64 => set_filter(Priority::compulsory_capture()),
+        63 => filter(ResultingPosition::no_piece_en_prise()),
+        62 => filter(ResultingPosition::keep_the_bishop_pair()),
+        61 => filter(ResultingPosition::king_stays_on_first_two_ranks()),
+        60 => filter(ResultingPosition::queen_cant_give_check()),
+        59 => filter(OpeningMove::knights_to_the_edges()),
+        58 => filter(OpeningMove::bongcloud_and_back()),
+        57 => filter(OpeningMove::bongcloud()),
+        56 => filter(OpeningMove::allow_fools_mate()),
+        55 => filter(OpeningMove::bring_both_rooks_out()),
+        54 => filter(OpeningMove::move_f_pawn_twice()),
+        53 => filter(OpeningMove::scholars_mate()),
+        52 => filter(OpeningMove::rush_b()),
+        51 => filter(OpeningMove::rush_a()),
+        50 => filter(OpeningMove::the_cheese_opening()),
+        49 => filter(OpeningMove::edge_pawns_two_squares()),
+        48 => filter(OpeningMove::g_and_f_pawn()),
+        47 => filter(OpeningMove::knight_and_back()),
+        46 => filter(OpeningMove::two_g_pawn_moves()),
+        45 => filter(MoveTo::cant_play_on_the_c_or_e_or_h_file()),
+        44 => filter(MoveTo::cant_play_on_the_c_or_h_file()),
+        43 => filter(MoveTo::cant_play_on_the_c_or_e_file()),
+        42 => filter(MoveTo::cant_play_on_the_e_or_h_file()),
+        41 => filter(MoveTo::cant_play_on_the_e_file()),
+        40 => filter(MoveTo::cant_play_on_the_h_file()),
+        39 => filter(MoveTo::cant_play_on_the_c_file()),
+        38 => filter(MoveTo::queen_can_only_move_to_light_squares()),
+        37 => filter(MoveTo::queen_can_only_move_to_dark_squares()),
+        36 => filter(MoveTo::king_can_only_move_to_light_squares()),
+        35 => filter(MoveTo::king_can_only_move_to_dark_squares()),
+        34 => filter(MoveTo::rooks_can_only_move_to_the_edges()),
+        33 => filter(MoveAfter::knight_cant_move_after_10()),
+        32 => filter(MoveAfter::knight_cant_move_after_15()),
+        31 => filter(MoveAfter::knight_cant_move_after_20()),
+        30 => filter(MoveAfter::bishop_cant_move_after_10()),
+        29 => filter(MoveAfter::bishop_cant_move_after_15()),
+        28 => filter(MoveAfter::bishop_cant_move_after_20()),
+        27 => filter(MoveAfter::rook_cant_move_after_15()),
+        26 => filter(MoveAfter::rook_cant_move_after_20()),
+        25 => filter(MoveAfter::rook_cant_move_after_25()),
+        24 => filter(MoveAfter::queen_cant_move_after_6()),
+        23 => filter(MoveAfter::queen_cant_move_after_9()),
+        22 => filter(MoveAfter::queen_cant_move_after_12()),
+        21 => filter(CantCapture::cant_capture_knights()),
+        20 => filter(CantCapture::cant_capture_bishops()),
+        19 => filter(CantCapture::cant_capture_rooks()),
+        18 => filter(CantCapture::pawns_cant_capture_pawns()),
+        17 => filter(CantCapture::pawns_can_only_capture_pawns()),
+        16 => filter(CantCapture::only_pawns_can_capture_pawns()),
+        15 => filter(CantCapture::queen_can_only_capture_bishops()),
+        14 => filter(CantCapture::queen_can_only_capture_knights()),
+        13 => filter(CantCapture::queen_can_only_capture_rooks()),
+        12 => filter(CantCapture::queen_can_only_capture_pawns()),
+        11 => filter(CantCapture::rooks_cant_capture_queens()),
+        10 => filter(CantCapture::bishops_cant_capture_queens()),
+        9 => filter(CantCapture::knights_cant_capture_queens()),
+        8 => filter(CantCapture::knights_cant_capture_anything()),
+        7 => filter(CantCapture::bishops_cant_capture_anything()),
+        6 => filter(CantCapture::rooks_cant_capture_anything()),
+        5 => filter(CantCapture::queen_cant_capture_anything()),
+        4 => filter(CantCapture::king_cant_capture_anything()),
+        3 => filter(CantCapture::pawns_cant_be_captured()),
+        2 => filter(CantCapture::only_king_can_capture_pawns()),
+        1 => filter(CantCapture::only_pawns_or_king_can_capture_pawns()),
+        _ => filter(NoFilter::new()),
+    }
+}
+
+// Rules that look at more than the position and the turn, like the moves played earlier, can
+// allow different moves in the same position on the same turn.
+pub fn depends_on_history(filter_id: i32) -> bool {
+    matches!(filter_id, 46..=59 | 67..=72 | 80 | 85..=87)
+}
+
+// The rules that spend a budget, the same ones the registry filters with.
+fn budget(filter_id: i32) -> Option<Budget> {
+    match filter_id {
+        68 => Some(Budget::eight_queen_moves()),
+        67 => Some(Budget::five_captures()),
+        _ => None,
+    }
+}
+
+// The rules with an obligation, the same ones the registry filters with.
+fn obligation(filter_id: i32) -> Option<Obligation> {
     match filter_id {
-        59 => OpeningMove::knights_to_the_edges().filter_moves(game),
-        58 => OpeningMove::bongcloud_and_back().filter_moves(game),
-        57 => OpeningMove::bongcloud().filter_moves(game),
-        56 => OpeningMove::allow_fools_mate().filter_moves(game),
-        55 => OpeningMove::bring_both_rooks_out().filter_moves(game),
-        54 => OpeningMove::move_f_pawn_twice().filter_moves(game),
-        53 => OpeningMove::scholars_mate().filter_moves(game),
-        52 => OpeningMove::rush_b().filter_moves(game),
-        51 => OpeningMove::rush_a().filter_moves(game),
-        50 => OpeningMove::the_cheese_opening().filter_moves(game),
-        49 => OpeningMove::edge_pawns_two_squares().filter_moves(game),
-        48 => OpeningMove::g_and_f_pawn().filter_moves(game),
-        47 => OpeningMove::knight_and_back().filter_moves(game),
-        46 => OpeningMove::two_g_pawn_moves().filter_moves(game),
-        45 => MoveTo::cant_play_on_the_c_or_e_or_h_file().filter_moves(game),
-        44 => MoveTo::cant_play_on_the_c_or_h_file().filter_moves(game),
-        43 => MoveTo::cant_play_on_the_c_or_e_file().filter_moves(game),
-        42 => MoveTo::cant_play_on_the_e_or_h_file().filter_moves(game),
-        41 => MoveTo::cant_play_on_the_e_file().filter_moves(game),
-        40 => MoveTo::cant_play_on_the_h_file().filter_moves(game),
-        39 => MoveTo::cant_play_on_the_c_file().filter_moves(game),
-        38 => MoveTo::queen_can_only_move_to_light_squares().filter_moves(game),
-        37 => MoveTo::queen_can_only_move_to_dark_squares().filter_moves(game),
-        36 => MoveTo::king_can_only_move_to_light_squares().filter_moves(game),
-        35 => MoveTo::king_can_only_move_to_dark_squares().filter_moves(game),
-        34 => MoveTo::rooks_can_only_move_to_the_edges().filter_moves(game),
-        33 => MoveAfter::knight_cant_move_after_10().filter_moves(game),
-        32 => MoveAfter::knight_cant_move_after_15().filter_moves(game),
-        31 => MoveAfter::knight_cant_move_after_20().filter_moves(game),
-        30 => MoveAfter::bishop_cant_move_after_10().filter_moves(game),
-        29 => MoveAfter::bishop_cant_move_after_15().filter_moves(game),
-        28 => MoveAfter::bishop_cant_move_after_20().filter_moves(game),
-        27 => MoveAfter::rook_cant_move_after_15().filter_moves(game),
-        26 => MoveAfter::rook_cant_move_after_20().filter_moves(game),
-        25 => MoveAfter::rook_cant_move_after_25().filter_moves(game),
-        24 => MoveAfter::queen_cant_move_after_6().filter_moves(game),
-        23 => MoveAfter::queen_cant_move_after_9().filter_moves(game),
-        22 => MoveAfter::queen_cant_move_after_12().filter_moves(game),
-        21 => CantCapture::cant_capture_knights().filter_moves(game),
-        20 => CantCapture::cant_capture_bishops().filter_moves(game),
-        19 => CantCapture::cant_capture_rooks().filter_moves(game),
-        18 => CantCapture::pawns_cant_capture_pawns().filter_moves(game),
-        17 => CantCapture::pawns_can_only_capture_pawns().filter_moves(game),
-        16 => CantCapture::only_pawns_can_capture_pawns().filter_moves(game),
-        15 => CantCapture::queen_can_only_capture_bishops().filter_moves(game),
-        14 => CantCapture::queen_can_only_capture_knights().filter_moves(game),
-        13 => CantCapture::queen_can_only_capture_rooks().filter_moves(game),
-        12 => CantCapture::queen_can_only_capture_pawns().filter_moves(game),
-        11 => CantCapture::rooks_cant_capture_queens().filter_moves(game),
-        10 => CantCapture::bishops_cant_capture_queens().filter_moves(game),
-        9 => CantCapture::knights_cant_capture_queens().filter_moves(game),
-        8 => CantCapture::knights_cant_capture_anything().filter_moves(game),
-        7 => CantCapture::bishops_cant_capture_anything().filter_moves(game),
-        6 => CantCapture::rooks_cant_capture_anything().filter_moves(game),
-        5 => CantCapture::queen_cant_capture_anything().filter_moves(game),
-        4 => CantCapture::king_cant_capture_anything().filter_moves(game),
-        3 => CantCapture::pawns_cant_be_captured().filter_moves(game),
-        2 => CantCapture::only_king_can_capture_pawns().filter_moves(game),
-        1 => CantCapture::only_pawns_or_king_can_capture_pawns().filter_moves(game),
-        _ => NoFilter::new().filter_moves(game),
+        87 => Some(Obligation::castle_and_develop()),
+        86 => Some(Obligation::both_knights_on_the_rim()),
+        85 => Some(Obligation::both_rooks_to_the_third_rank()),
+        _ => None,
     }
 }
 
+// What is left of the rule's budget for this color, None for rules without one.
+pub fn remaining_budget(filter_id: i32, context: &FilterContext, color: Color) -> Option<u16> {
+    budget(filter_id).map(|budget| budget.remaining(&context.history, color))
+}
+
+// How far this color is with the rule's obligation, None for rules without one.
+pub fn obligation_progress(
+    filter_id: i32,
+    context: &FilterContext,
+    color: Color,
+) -> Option<Progress> {
+    obligation(filter_id)
+        .map(|obligation| obligation.progress(&context.history, &context.board, color))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chess::{Game, Square};
     use std::str::FromStr;
-    use chess::Square;
 
     #[test]
     fn no_filter_test() {
         let game = Game::new();
-        assert_eq!(generate_moves(0, &game).len(), 20);
+        assert_eq!(
+            generate_moves(0, &FilterContext::from_game(&game)).len(),
+            20
+        );
     }
 
     #[test]
@@ -93,7 +220,7 @@ mod tests {
         let mut game = Game::new();
         game.make_move(ChessMove::new(Square::E2, Square::E4, None));
         game.make_move(ChessMove::new(Square::D7, Square::D5, None));
-        assert!(!generate_moves(3, &game)
+        assert!(!generate_moves(3, &FilterContext::from_game(&game))
             .iter()
             .any(|m| m.get_source() == Square::E4 && m.get_dest() == Square::D5));
     }
@@ -111,17 +238,17 @@ mod tests {
         game.make_move(ChessMove::new(Square::G8, Square::F6, None));
         game.make_move(ChessMove::new(Square::F3, Square::G1, None));
         game.make_move(ChessMove::new(Square::F6, Square::G8, None));
-        assert!(generate_moves(24, &game)
+        assert!(generate_moves(24, &FilterContext::from_game(&game))
             .iter()
             .any(|m| m.get_source() == Square::D1 && m.get_dest() == Square::H5)); // move 5 can move
         game.make_move(ChessMove::new(Square::G1, Square::F3, None));
         game.make_move(ChessMove::new(Square::G8, Square::F6, None));
         game.make_move(ChessMove::new(Square::F3, Square::G1, None));
         game.make_move(ChessMove::new(Square::F6, Square::G8, None));
-        assert!(!generate_moves(24, &game)
+        assert!(!generate_moves(24, &FilterContext::from_game(&game))
             .iter()
             .any(|m| m.get_source() == Square::D1 && m.get_dest() == Square::H5)); // move 7 can't
-        assert!(generate_moves(23, &game)
+        assert!(generate_moves(23, &FilterContext::from_game(&game))
             .iter()
             .any(|m| m.get_source() == Square::D1 && m.get_dest() == Square::H5));
         // Move is there with other rule
@@ -130,10 +257,10 @@ mod tests {
     #[test]
     fn queen_cant_move_to_light() {
         let game = Game::from_str("7k/8/8/8/8/5Q2/8/K7 w - - 0 1").unwrap();
-        assert!(generate_moves(37, &game)
+        assert!(generate_moves(37, &FilterContext::from_game(&game))
             .iter()
             .any(|m| m.get_source() == Square::F3 && m.get_dest() == Square::E3));
-        assert!(!generate_moves(37, &game)
+        assert!(!generate_moves(37, &FilterContext::from_game(&game))
             .iter()
             .any(|m| m.get_source() == Square::F3 && m.get_dest() == Square::E2));
     }
@@ -0,0 +1,109 @@
+use crate::filters::context::FilterContext;
+use crate::move_generator::{depends_on_history, generate_moves};
+use chess::ChessMove;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// What the allowed moves of a position were computed for: the rule, the number of actions so far
+// and the position's hash.
+type PositionKey = (i32, usize, u64);
+
+// The allowed moves of one game's current position, so they are only computed once per action.
+// ChessGame clears it on every action, the key also catches changes made to the game directly.
+#[derive(Default)]
+pub struct PositionCache(Mutex<Option<(PositionKey, Vec<ChessMove>)>>);
+
+impl PositionCache {
+    pub fn get_or_insert(
+        &self,
+        key: PositionKey,
+        generate: impl FnOnce() -> Vec<ChessMove>,
+    ) -> Vec<ChessMove> {
+        let mut cached = self.0.lock().unwrap();
+        match cached.as_ref() {
+            Some((cached_key, moves)) if *cached_key == key => moves.clone(),
+            _ => {
+                let moves = generate();
+                *cached = Some((key, moves.clone()));
+                moves
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+// Allowed moves shared between games, for when many games are played in one process like the
+// simulator and the engine do. Keyed by the position's hash, the rule and the turn, so the rules
+// that look at the moves played earlier are always generated again.
+pub struct MoveCache {
+    moves: Mutex<HashMap<(u64, i32, u16), Vec<ChessMove>>>,
+    capacity: usize,
+}
+
+impl MoveCache {
+    // Starts over when it holds this many positions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            moves: Mutex::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    pub fn generate_moves(&self, filter_id: i32, context: &FilterContext) -> Vec<ChessMove> {
+        if depends_on_history(filter_id) {
+            return generate_moves(filter_id, context);
+        }
+        let key = (context.board.get_hash(), filter_id, context.turn);
+        if let Some(moves) = self.moves.lock().unwrap().get(&key) {
+            return moves.clone();
+        }
+        let moves = generate_moves(filter_id, context);
+        let mut cached = self.moves.lock().unwrap();
+        if cached.len() >= self.capacity {
+            cached.clear();
+        }
+        cached.insert(key, moves.clone());
+        moves
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Game, Square};
+
+    #[test]
+    fn position_cache_follows_the_key() {
+        let cache = PositionCache::default();
+        let first = cache.get_or_insert((0, 0, 1), Vec::new);
+        let e4 = ChessMove::new(Square::E2, Square::E4, None);
+        let again = cache.get_or_insert((0, 0, 1), || vec![e4]);
+        assert_eq!(first, again);
+        assert_eq!(cache.get_or_insert((0, 1, 1), || vec![e4]), vec![e4]);
+        cache.clear();
+        assert!(cache.get_or_insert((0, 1, 1), Vec::new).is_empty());
+    }
+
+    #[test]
+    fn only_rules_without_history_are_shared() {
+        let cache = MoveCache::new(10);
+        let context = FilterContext::from_game(&Game::new());
+        assert_eq!(cache.generate_moves(37, &context).len(), 20);
+        assert_eq!(cache.generate_moves(37, &context).len(), 20);
+        assert_eq!(cache.len(), 1);
+        // Stealo 69: the same piece can't move twice in a row.
+        cache.generate_moves(69, &context);
+        assert_eq!(cache.len(), 1);
+    }
+}
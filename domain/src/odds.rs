@@ -0,0 +1,179 @@
+use crate::clock::Clock;
+use crate::filters::identity::starting_square;
+use chess::{Board, BoardBuilder, CastleRights, Color, Square};
+
+// Classic handicaps: the player giving odds starts without some material.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum MaterialOdds {
+    #[default]
+    None,
+    // Without the f-pawn, and the opponent moves first.
+    PawnAndMove,
+    // Without the queen's knight.
+    Knight,
+    // Without the queen's rook.
+    Rook,
+    Queen,
+}
+
+impl MaterialOdds {
+    pub const ALL: [MaterialOdds; 5] = [
+        MaterialOdds::None,
+        MaterialOdds::PawnAndMove,
+        MaterialOdds::Knight,
+        MaterialOdds::Rook,
+        MaterialOdds::Queen,
+    ];
+
+    pub fn from_id(id: i16) -> Self {
+        match id {
+            1 => MaterialOdds::PawnAndMove,
+            2 => MaterialOdds::Knight,
+            3 => MaterialOdds::Rook,
+            4 => MaterialOdds::Queen,
+            _ => MaterialOdds::None,
+        }
+    }
+
+    pub fn id(self) -> i16 {
+        match self {
+            MaterialOdds::None => 0,
+            MaterialOdds::PawnAndMove => 1,
+            MaterialOdds::Knight => 2,
+            MaterialOdds::Rook => 3,
+            MaterialOdds::Queen => 4,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MaterialOdds::None => "No material odds",
+            MaterialOdds::PawnAndMove => "Pawn and move",
+            MaterialOdds::Knight => "Knight odds",
+            MaterialOdds::Rook => "Rook odds",
+            MaterialOdds::Queen => "Queen odds",
+        }
+    }
+
+    // Estimated like the elo of an Elo Stealo rule: what the player giving the odds loses.
+    pub fn elo(self) -> i32 {
+        match self {
+            MaterialOdds::None => 0,
+            MaterialOdds::PawnAndMove => 250,
+            MaterialOdds::Knight => 750,
+            MaterialOdds::Rook => 1000,
+            MaterialOdds::Queen => 1500,
+        }
+    }
+
+    pub fn start_position(self, giver: Color) -> Board {
+        let (square, lost_rights) = match self {
+            MaterialOdds::None => return Board::default(),
+            MaterialOdds::PawnAndMove => (Square::F2, CastleRights::NoRights),
+            MaterialOdds::Knight => (Square::B1, CastleRights::NoRights),
+            MaterialOdds::Rook => (Square::A1, CastleRights::QueenSide),
+            MaterialOdds::Queen => (Square::D1, CastleRights::NoRights),
+        };
+        let square = starting_square(square, giver);
+        let mut builder = BoardBuilder::from(Board::default());
+        let rights = builder.get_castle_rights(giver).remove(lost_rights);
+        builder.clear_square(square).castle_rights(giver, rights);
+        // Black already moves second, so giving the move only changes something for white.
+        if self == MaterialOdds::PawnAndMove && giver == Color::White {
+            builder.side_to_move(Color::Black);
+        }
+        Board::try_from(&builder).expect("odds leave a valid position")
+    }
+}
+
+// The player giving odds gets less time on the clock, like 5 against 3 minutes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeOdds {
+    pub minutes: u16,
+    pub giver_minutes: u16,
+}
+
+impl TimeOdds {
+    pub const PRESETS: [TimeOdds; 4] = [
+        TimeOdds::new(5, 4),
+        TimeOdds::new(5, 3),
+        TimeOdds::new(5, 2),
+        TimeOdds::new(5, 1),
+    ];
+
+    pub const fn new(minutes: u16, giver_minutes: u16) -> Self {
+        Self {
+            minutes,
+            giver_minutes,
+        }
+    }
+
+    pub fn name(self) -> String {
+        format!("{} vs {} minutes", self.minutes, self.giver_minutes)
+    }
+
+    // Every halving of the time is estimated at 400 elo, rounded to 50.
+    pub fn elo(self) -> i32 {
+        if self.giver_minutes >= self.minutes {
+            return 0;
+        }
+        let ratio = self.minutes as f64 / self.giver_minutes.max(1) as f64;
+        ((400.0 * ratio.log2() / 50.0).round() * 50.0) as i32
+    }
+
+    pub fn clock(self, giver: Color) -> Clock {
+        let minutes = |color: Color| {
+            let minutes = if color == giver {
+                self.giver_minutes
+            } else {
+                self.minutes
+            };
+            minutes as u64 * 60_000
+        };
+        Clock::new(minutes(Color::White), minutes(Color::Black))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{MoveGen, Piece};
+
+    #[test]
+    fn knight_odds_remove_the_queens_knight() {
+        let board = MaterialOdds::Knight.start_position(Color::Black);
+        assert_eq!(board.piece_on(Square::B8), None);
+        assert_eq!(board.piece_on(Square::B1), Some(Piece::Knight));
+        assert_eq!(board.side_to_move(), Color::White);
+    }
+
+    #[test]
+    fn rook_odds_remove_queenside_castling() {
+        let board = MaterialOdds::Rook.start_position(Color::White);
+        assert_eq!(board.castle_rights(Color::White), CastleRights::KingSide);
+        assert_eq!(board.castle_rights(Color::Black), CastleRights::Both);
+    }
+
+    #[test]
+    fn pawn_and_move_lets_black_start() {
+        let board = MaterialOdds::PawnAndMove.start_position(Color::White);
+        assert_eq!(board.piece_on(Square::F2), None);
+        assert_eq!(board.side_to_move(), Color::Black);
+        assert_eq!(MoveGen::new_legal(&board).len(), 20);
+    }
+
+    #[test]
+    fn time_odds_elo_grows_with_the_ratio() {
+        assert_eq!(TimeOdds::new(5, 5).elo(), 0);
+        assert_eq!(TimeOdds::new(5, 3).elo(), 300);
+        assert_eq!(TimeOdds::new(10, 5).elo(), 400);
+        assert_eq!(TimeOdds::new(5, 1).elo(), 950);
+    }
+
+    #[test]
+    fn giver_gets_less_time() {
+        let clock = TimeOdds::new(5, 3).clock(Color::Black);
+        assert_eq!(clock.white_ms, 300_000);
+        assert_eq!(clock.black_ms, 180_000);
+    }
+}
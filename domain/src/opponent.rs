@@ -0,0 +1,74 @@
+use chess::Color;
+
+// Who plays the other side of a local game.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Opponent {
+    // Both players share the screen.
+    #[default]
+    Hotseat,
+    // The computer plays this color, the level picks how well, see the engine crate.
+    Bot {
+        color: Color,
+        level: u8,
+    },
+    // A UCI engine configured on the server plays this color, under the same rules.
+    Uci {
+        color: Color,
+    },
+}
+
+impl Opponent {
+    // Stored as a kind, the bot's color and its level.
+    pub fn from_parts(kind: i16, color: i16, level: i16) -> Self {
+        let color = match color {
+            1 => Color::Black,
+            _ => Color::White,
+        };
+        match kind {
+            1 => Opponent::Bot {
+                color,
+                level: level.clamp(0, u8::MAX as i16) as u8,
+            },
+            2 => Opponent::Uci { color },
+            _ => Opponent::Hotseat,
+        }
+    }
+
+    pub fn to_parts(self) -> (i16, i16, i16) {
+        match self {
+            Opponent::Hotseat => (0, 0, 0),
+            Opponent::Bot { color, level } => (1, color.to_index() as i16, level as i16),
+            Opponent::Uci { color } => (2, color.to_index() as i16, 0),
+        }
+    }
+
+    // The color the computer plays, if any.
+    pub fn bot_color(self) -> Option<Color> {
+        match self {
+            Opponent::Hotseat => None,
+            Opponent::Bot { color, .. } | Opponent::Uci { color } => Some(color),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_round_trip() {
+        for opponent in [
+            Opponent::Hotseat,
+            Opponent::Bot {
+                color: Color::Black,
+                level: 3,
+            },
+            Opponent::Uci {
+                color: Color::White,
+            },
+        ] {
+            let (kind, color, level) = opponent.to_parts();
+            assert_eq!(opponent, Opponent::from_parts(kind, color, level));
+        }
+    }
+}
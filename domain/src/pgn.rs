@@ -0,0 +1,151 @@
+// Reads the games of a PGN file, for measuring rules on games played elsewhere. Only the main
+// line is kept, comments, variations and annotations are skipped.
+use crate::filters::history::History;
+use chess::{Board, ChessMove, Game};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub enum PgnError {
+    InvalidFen(String),
+    // The game it is in, counting from 1, and the move as written.
+    IllegalMove(usize, String),
+}
+
+pub fn parse_pgn(pgn: &str) -> Result<Vec<History>, PgnError> {
+    let mut reader = Reader::default();
+    let mut chars = pgn.chars();
+    let mut token = String::new();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() || "[{;(".contains(c) {
+            reader.token(&token)?;
+            token.clear();
+        }
+        match c {
+            '[' => {
+                // Tags start the next game, when the last one had no result.
+                if reader.game.is_some() {
+                    reader.finish();
+                }
+                let tag: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                if let Some(fen) = tag.strip_prefix("FEN") {
+                    reader.fen = Some(fen.trim().trim_matches('"').to_string());
+                }
+            }
+            '{' => chars.by_ref().take_while(|&c| c != '}').for_each(drop),
+            ';' => chars.by_ref().take_while(|&c| c != '\n').for_each(drop),
+            '(' => {
+                let mut depth = 1;
+                for c in chars.by_ref() {
+                    depth += (c == '(') as i32 - (c == ')') as i32;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => token.push(c),
+        }
+    }
+    reader.token(&token)?;
+    reader.finish();
+    Ok(reader.games)
+}
+
+#[derive(Default)]
+struct Reader {
+    games: Vec<History>,
+    game: Option<History>,
+    // From the tags of the game being read, games without one start from the normal position.
+    fen: Option<String>,
+}
+
+impl Reader {
+    fn token(&mut self, token: &str) -> Result<(), PgnError> {
+        if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+            self.finish();
+            return Ok(());
+        }
+        // Move numbers can be stuck to the move, like 12.e4 or 12...e5.
+        let san = match token.rfind('.') {
+            Some(dot) if token[..dot].chars().all(|c| c.is_ascii_digit() || c == '.') => {
+                &token[dot + 1..]
+            }
+            _ => token,
+        };
+        let san = san.trim_end_matches(['+', '#', '!', '?']).replace('=', "");
+        let san = match san.as_str() {
+            "0-0" => "O-O".to_string(),
+            "0-0-0" => "O-O-O".to_string(),
+            _ => san,
+        };
+        if san.is_empty() || san.starts_with('$') || san == "e.p." {
+            return Ok(());
+        }
+        let game = match self.game.as_mut() {
+            Some(game) => game,
+            None => {
+                let start = match self.fen.as_deref() {
+                    Some(fen) => {
+                        Board::from_str(fen).map_err(|_| PgnError::InvalidFen(fen.to_string()))?
+                    }
+                    None => Board::default(),
+                };
+                self.game
+                    .insert(History::new(start, &Game::new_with_board(start)))
+            }
+        };
+        let chess_move = ChessMove::from_san(game.board(), &san)
+            .map_err(|_| PgnError::IllegalMove(self.games.len() + 1, token.to_string()))?;
+        game.push(chess_move);
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        if let Some(game) = self.game.take() {
+            self.games.push(game);
+        }
+        self.fen = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Square;
+
+    #[test]
+    fn reads_main_lines_of_every_game() {
+        let pgn = r#"[Event "Casual"]
+[White "A"]
+[Black "B"]
+
+1. e4 e5 2. Nf3 {the usual} Nc6 (2... d6 3. d4) 3. Bb5 a6?! $6 4. O-O 1-0
+
+[Event "Odds"]
+[FEN "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w Qkq - 0 1"]
+[SetUp "1"]
+
+1.d4 d5 2.c4 dxc4 ; gambit accepted
+3.e3 *
+"#;
+        let games = parse_pgn(pgn).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].len(), 7);
+        assert_eq!(
+            games[0].moves().last().unwrap().1,
+            ChessMove::new(Square::E1, Square::G1, None)
+        );
+        assert_eq!(games[1].len(), 5);
+        assert_eq!(games[1].start().piece_on(Square::H1), None);
+    }
+
+    #[test]
+    fn illegal_moves_name_their_game() {
+        let pgn = "1. e4 e5 1-0\n\n1. e4 e5 2. Ke3 0-1";
+        assert_eq!(
+            parse_pgn(pgn).err(),
+            Some(PgnError::IllegalMove(2, "Ke3".to_string()))
+        );
+        assert!(parse_pgn("[FEN \"nonsense\"]\n1. e4").is_err());
+    }
+}
@@ -0,0 +1,148 @@
+// How much a rule gets in the way, measured on real positions instead of played out games: the
+// share of legal moves it takes away, how often it forbids the move an engine would play and how
+// often it leaves nothing at all.
+use crate::filters::context::FilterContext;
+use crate::filters::history::History;
+use crate::move_generator::generate_moves;
+use chess::{ChessMove, Game};
+
+// Counts over the positions of a corpus for one rule, the rates are worked out from them so
+// counts of different parts of a corpus can be merged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Restrictiveness {
+    pub rule_id: i32,
+    // Positions with legal moves, the others don't say anything about a rule.
+    pub positions: usize,
+    removed: f64,
+    // Positions the engine found a move in, and how many times the rule forbade it.
+    best_moves: usize,
+    best_moves_forbidden: usize,
+    no_moves: usize,
+}
+
+impl Restrictiveness {
+    pub fn new(rule_id: i32) -> Self {
+        Self {
+            rule_id,
+            ..Self::default()
+        }
+    }
+
+    // The average share of the legal moves the rule forbids.
+    pub fn removed(&self) -> f64 {
+        self.removed / self.positions.max(1) as f64
+    }
+
+    // None when no engine was asked.
+    pub fn best_move_forbidden(&self) -> Option<f64> {
+        (self.best_moves > 0).then(|| self.best_moves_forbidden as f64 / self.best_moves as f64)
+    }
+
+    // How often the player under the rule would lose right there.
+    pub fn no_moves(&self) -> f64 {
+        self.no_moves as f64 / self.positions.max(1) as f64
+    }
+
+    pub fn merge(&mut self, other: &Restrictiveness) {
+        self.positions += other.positions;
+        self.removed += other.removed;
+        self.best_moves += other.best_moves;
+        self.best_moves_forbidden += other.best_moves_forbidden;
+        self.no_moves += other.no_moves;
+    }
+}
+
+// Every position of every game counts, with the moves before it for the rules that look back.
+// Once a player made a move the rule doesn't allow, the rest of the game couldn't have been
+// played under it, so the rule stops counting that player's positions in the game.
+// The best move is asked once per position, pass |_| None to leave it out.
+pub fn measure(
+    games: &[History],
+    rule_ids: &[i32],
+    best_move: impl Fn(&History) -> Option<ChessMove>,
+) -> Vec<Restrictiveness> {
+    let mut results: Vec<Restrictiveness> = rule_ids
+        .iter()
+        .map(|&id| Restrictiveness::new(id))
+        .collect();
+    for game in games {
+        // Per rule, the colors that already broke it in this game.
+        let mut broken = vec![[false; 2]; results.len()];
+        let played: Vec<ChessMove> = game.moves().map(|(_, chess_move)| *chess_move).collect();
+        // The game is played again on one history, a move at a time.
+        let mut history = History::new(*game.start(), &Game::new_with_board(*game.start()));
+        for next in played.iter().map(Some).chain([None]) {
+            let context = FilterContext::new(history, None);
+            let side = context.side.to_index();
+            let legal = context.legal_moves().len();
+            if legal > 0 && !broken.iter().all(|colors| colors[side]) {
+                let best = best_move(&context.history);
+                for (result, colors) in results.iter_mut().zip(broken.iter_mut()) {
+                    if colors[side] {
+                        continue;
+                    }
+                    let allowed = generate_moves(result.rule_id, &context);
+                    result.positions += 1;
+                    result.removed += 1.0 - allowed.len() as f64 / legal as f64;
+                    result.no_moves += allowed.is_empty() as usize;
+                    if let Some(best) = best {
+                        result.best_moves += 1;
+                        result.best_moves_forbidden += !allowed.contains(&best) as usize;
+                    }
+                    colors[side] = next.is_some_and(|next| !allowed.contains(next));
+                }
+            }
+            history = context.history;
+            if let Some(next) = next {
+                history.push(*next);
+            }
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::parse_pgn;
+    use chess::Square;
+
+    #[test]
+    fn counts_removed_and_forbidden_moves() {
+        let games = parse_pgn("1. e3 e5 *").unwrap();
+        let e4 = ChessMove::new(Square::E2, Square::E4, None);
+        let results = measure(&games, &[0], |_| Some(e4));
+        assert_eq!(results[0].positions, 3);
+        assert_eq!(results[0].removed(), 0.0);
+        assert_eq!(results[0].no_moves(), 0.0);
+        // Only white can play e2-e4, and only at the start.
+        assert_eq!(results[0].best_move_forbidden(), Some(2.0 / 3.0));
+    }
+
+    #[test]
+    fn positions_after_breaking_the_rule_dont_count() {
+        // Stealo 57: begin with e2-e4 or e7-e5. Both players break it with their first move, so
+        // only the starting position of each counts.
+        let games = parse_pgn("1. d4 d5 2. e4 e5 *").unwrap();
+        let results = measure(&games, &[57, 0], |_| None);
+        assert_eq!(results[0].positions, 2);
+        assert_eq!(results[0].removed(), 19.0 / 20.0);
+        assert_eq!(results[1].positions, 5);
+    }
+
+    #[test]
+    fn lockouts_are_counted_and_merged() {
+        // Stealo 57: begin with e2-e4 or e7-e5, then move the king. White can't begin with it here.
+        let pgn = r#"[FEN "rnbqkbnr/pppppppp/8/8/8/4P3/PPPP1PPP/RNBQKBNR b KQkq - 0 1"]
+1... e5 *"#;
+        let blocked = measure(&parse_pgn(pgn).unwrap(), &[57], |_| None);
+        assert_eq!(blocked[0].removed(), (19.0 / 20.0 + 1.0) / 2.0);
+        assert_eq!(blocked[0].no_moves(), 0.5);
+        assert_eq!(blocked[0].best_move_forbidden(), None);
+        let mut merged = Restrictiveness::new(57);
+        merged.merge(&blocked[0]);
+        merged.merge(&blocked[0]);
+        assert_eq!(merged.positions, 4);
+        assert_eq!(merged.no_moves(), blocked[0].no_moves());
+    }
+}
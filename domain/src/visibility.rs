@@ -0,0 +1,53 @@
+// Decides when the players get to see each other's Elo Stealo rule.
+// A player always knows their own rule, this only concerns the opponent's.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum RuleVisibility {
+    // Both rules are known from the start.
+    Open,
+    // Rules are revealed when the game ends.
+    #[default]
+    Hidden,
+    // Rules are revealed once both players have made this many moves.
+    RevealAfter(u16),
+    // A rule is revealed the first time its owner tries a legal move the rule forbids.
+    RevealOnForbiddenAttempt,
+}
+
+impl RuleVisibility {
+    // Policies are stored as a kind and an optional move count.
+    pub fn from_parts(kind: i16, moves: i16) -> Self {
+        match kind {
+            0 => RuleVisibility::Open,
+            2 => RuleVisibility::RevealAfter(moves.max(0) as u16),
+            3 => RuleVisibility::RevealOnForbiddenAttempt,
+            _ => RuleVisibility::Hidden,
+        }
+    }
+
+    pub fn to_parts(self) -> (i16, i16) {
+        match self {
+            RuleVisibility::Open => (0, 0),
+            RuleVisibility::Hidden => (1, 0),
+            RuleVisibility::RevealAfter(moves) => (2, moves.min(i16::MAX as u16) as i16),
+            RuleVisibility::RevealOnForbiddenAttempt => (3, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_round_trip() {
+        for visibility in [
+            RuleVisibility::Open,
+            RuleVisibility::Hidden,
+            RuleVisibility::RevealAfter(10),
+            RuleVisibility::RevealOnForbiddenAttempt,
+        ] {
+            let (kind, moves) = visibility.to_parts();
+            assert_eq!(visibility, RuleVisibility::from_parts(kind, moves));
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// Plays engines with and without Elo Stealo rules against each other and prints what each rule
+// costs, as CSV or JSON. The elo_penalty column is what the rules table calls elo.
+//
+//   cargo run --release -p engine --bin simulate -- --rules 3,5,24 --games 1000 --format csv
+//
+// Players are bot:<level> for the built-in engine or uci:<path> for a UCI engine.
+//
+// With --corpus it plays nothing and measures how restrictive the rules are on the positions of a
+// PGN file instead, the player finds the best moves there, or none to leave that out.
+use domain::chessgame::is_known_rule;
+use domain::move_generator::HIGHEST_FILTER_ID;
+use domain::pgn::parse_pgn;
+use domain::restrictiveness::Restrictiveness;
+use engine::simulation::{measure_restrictiveness, simulate, Player, RuleReport, Settings};
+use engine::strength::Strength;
+use engine::uci::UciEngine;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: simulate [--rules all|<id,id,...>] [--games <n>] \
+[--player bot:<level>|uci:<path>] [--opponent bot:<level>|uci:<path>] [--movetime <ms>] \
+[--opening-plies <n>] [--max-plies <n>] [--threads <n>] [--seed <n>] [--format csv|json] \
+[--corpus <file.pgn>]";
+
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Arguments {
+    rule_ids: Vec<i32>,
+    settings: Settings,
+    format: Format,
+    corpus: Option<String>,
+    // Without one, the corpus is measured without best moves.
+    best_move_engine: Option<Player>,
+}
+
+fn main() -> ExitCode {
+    let arguments = match parse(std::env::args().skip(1).collect()) {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(corpus) = &arguments.corpus {
+        return match restrictiveness(corpus, &arguments) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(message) => {
+                eprintln!("{message}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+    let reports = match simulate(&arguments.rule_ids, &arguments.settings) {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("Simulation failed: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match arguments.format {
+        Format::Csv => {
+            println!("{}", RuleReport::CSV_HEADER);
+            for report in reports {
+                println!("{}", report.to_csv());
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&reports).unwrap()),
+    }
+    ExitCode::SUCCESS
+}
+
+fn restrictiveness(corpus: &str, arguments: &Arguments) -> Result<(), String> {
+    let pgn = std::fs::read_to_string(corpus).map_err(|e| format!("Can't read {corpus}: {e}"))?;
+    let games = parse_pgn(&pgn).map_err(|e| format!("Can't read {corpus}: {e:?}"))?;
+    let results = measure_restrictiveness(
+        &games,
+        &arguments.rule_ids,
+        arguments.best_move_engine.as_ref(),
+        arguments.settings.threads,
+    )
+    .map_err(|e| format!("Measuring failed: {e}"))?;
+    match arguments.format {
+        Format::Csv => {
+            println!("rule_id,positions,removed,best_move_forbidden,no_moves");
+            for result in &results {
+                let best_move_forbidden = result
+                    .best_move_forbidden()
+                    .map_or(String::new(), |rate| format!("{rate:.4}"));
+                println!(
+                    "{},{},{:.4},{},{:.4}",
+                    result.rule_id,
+                    result.positions,
+                    result.removed(),
+                    best_move_forbidden,
+                    result.no_moves()
+                );
+            }
+        }
+        Format::Json => {
+            let results: Vec<_> = results.iter().map(to_json).collect();
+            println!("{}", serde_json::to_string_pretty(&results).unwrap());
+        }
+    }
+    Ok(())
+}
+
+fn to_json(result: &Restrictiveness) -> serde_json::Value {
+    serde_json::json!({
+        "rule_id": result.rule_id,
+        "positions": result.positions,
+        "removed": result.removed(),
+        "best_move_forbidden": result.best_move_forbidden(),
+        "no_moves": result.no_moves(),
+    })
+}
+
+fn parse(args: Vec<String>) -> Result<Arguments, String> {
+    let mut rule_ids: Vec<i32> = (1..=HIGHEST_FILTER_ID).collect();
+    let mut settings = Settings::default();
+    let mut format = Format::Csv;
+    let mut corpus = None;
+    let mut movetime_ms = 100;
+    let mut players = ("bot:1".to_string(), "bot:1".to_string());
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+        let number = || {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("{value:?} is not a number for {flag}"))
+        };
+        match flag.as_str() {
+            "--rules" if value == "all" => rule_ids = (1..=HIGHEST_FILTER_ID).collect(),
+            "--rules" => {
+                rule_ids = value
+                    .split(',')
+                    .map(|id| id.trim().parse().ok().filter(|&id| is_known_rule(id)))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| format!("{value:?} are not known rule ids"))?
+            }
+            "--games" => settings.games = number()?,
+            "--player" => players.0 = value,
+            "--opponent" => players.1 = value,
+            "--movetime" => movetime_ms = number()? as u64,
+            "--opening-plies" => settings.opening_plies = number()?,
+            "--max-plies" => settings.max_plies = number()?,
+            "--threads" => settings.threads = number()?,
+            "--seed" => settings.seed = number()? as u64,
+            "--format" if value == "csv" => format = Format::Csv,
+            "--format" if value == "json" => format = Format::Json,
+            "--corpus" => corpus = Some(value),
+            _ => return Err(format!("Unknown argument {flag} {value}")),
+        }
+    }
+    let best_move_engine = match players.0.as_str() {
+        "none" if corpus.is_some() => None,
+        spec => Some(player(spec, movetime_ms)?),
+    };
+    settings.player = best_move_engine
+        .clone()
+        .unwrap_or(Player::Bot(Strength::Beginner));
+    settings.opponent = player(&players.1, movetime_ms)?;
+    Ok(Arguments {
+        rule_ids,
+        settings,
+        format,
+        corpus,
+        best_move_engine,
+    })
+}
+
+fn player(spec: &str, movetime_ms: u64) -> Result<Player, String> {
+    match spec.split_once(':') {
+        Some(("bot", level)) => level
+            .parse()
+            .map(|level| Player::Bot(Strength::from_level(level)))
+            .map_err(|_| format!("{level:?} is not a bot level")),
+        Some(("uci", path)) => Ok(Player::Uci(UciEngine::new(path, movetime_ms))),
+        _ => Err(format!("{spec:?} is not a player")),
+    }
+}
@@ -0,0 +1,31 @@
+use chess::{BitBoard, Board, Color, Piece};
+use domain::filters::material::material;
+
+// Squares c3 to f6, pieces there control more of the board.
+const CENTER: BitBoard = BitBoard(0x0000_3C3C_3C3C_0000);
+
+// In centipawns, from the side to move's point of view.
+pub fn evaluate(board: &Board) -> i32 {
+    let side = board.side_to_move();
+    score(board, side) - score(board, !side)
+}
+
+fn score(board: &Board, color: Color) -> i32 {
+    let own = board.color_combined(color);
+    let centered = own & CENTER & !board.pieces(Piece::King);
+    material(board, color) as i32 * 100 + centered.popcnt() as i32 * 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn material_and_center_count() {
+        assert_eq!(evaluate(&Board::default()), 0);
+        // White is a queen up, but black has a pawn in the center.
+        let board = Board::from_str("4k3/8/8/3p4/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert_eq!(evaluate(&board), -790);
+    }
+}
@@ -0,0 +1,8 @@
+// A computer opponent that plays under its own Elo Stealo rule. Candidate moves come from the
+// domain's rule filtered generator, so having no allowed moves is a loss like it is in a game.
+// An external UCI engine can take its place, it is only allowed to search the same moves.
+pub mod evaluation;
+pub mod search;
+pub mod simulation;
+pub mod strength;
+pub mod uci;
@@ -0,0 +1,162 @@
+use crate::evaluation::evaluate;
+use crate::strength::Strength;
+use chess::{BoardStatus, ChessMove};
+use domain::filters::context::FilterContext;
+use domain::filters::history::{is_capture, History};
+use domain::move_generator::generate_moves;
+
+// Worse than any evaluation, a mate sooner is worth more than one later.
+pub const MATE: i32 = 100_000;
+
+pub struct Engine {
+    pub strength: Strength,
+    // Picks between moves that are about as good, the same seed plays the same game.
+    pub seed: u64,
+}
+
+impl Engine {
+    pub fn new(strength: Strength, seed: u64) -> Self {
+        Self { strength, seed }
+    }
+
+    // The move for the side to move, None when its rule leaves it without moves.
+    // The rules are the ones the engine plays against, in Color::to_index order. An opponent's
+    // rule that is still hidden should be passed as 0, the engine can't know it either.
+    pub fn best_move(&self, history: &History, rules: [i32; 2]) -> Option<ChessMove> {
+        let mut search = Search { rules };
+        let context = FilterContext::new(history.clone(), None);
+        let moves = search.ordered_moves(&context);
+        let mut history = context.history;
+        let depth = self.strength.depth().max(1);
+        let margin = self.strength.margin();
+        let mut best = -MATE;
+        let mut scored = Vec::with_capacity(moves.len());
+        for chess_move in moves {
+            history.push(chess_move);
+            // Moves that can't come within the margin of the best one only need an upper bound.
+            let alpha = best.saturating_sub(margin + 1);
+            let score = -search.negamax(&mut history, depth - 1, -MATE, -alpha, 1);
+            history.pop();
+            best = best.max(score);
+            scored.push((chess_move, score));
+        }
+        let candidates: Vec<ChessMove> = scored
+            .into_iter()
+            .filter(|(_, score)| *score >= best - margin)
+            .map(|(chess_move, _)| chess_move)
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let pick = (self.seed ^ context.board.get_hash()) % candidates.len() as u64;
+        Some(candidates[pick as usize])
+    }
+}
+
+struct Search {
+    rules: [i32; 2],
+}
+
+impl Search {
+    // Captures first, they are the likeliest to cut off the rest.
+    fn ordered_moves(&self, context: &FilterContext) -> Vec<ChessMove> {
+        let mut moves = generate_moves(self.rules[context.side.to_index()], context);
+        moves.sort_by_key(|chess_move| !is_capture(&context.board, chess_move));
+        moves
+    }
+
+    // Searches the position at the end of the history, which is the same again when it returns.
+    fn negamax(
+        &mut self,
+        history: &mut History,
+        depth: u8,
+        mut alpha: i32,
+        beta: i32,
+        ply: i32,
+    ) -> i32 {
+        // The context holds the history while the moves are worked out, then hands it back.
+        let context = FilterContext::new(std::mem::take(history), None);
+        let moves = self.ordered_moves(&context);
+        *history = context.history;
+        // No allowed moves loses, unless it is a real stalemate.
+        if moves.is_empty() {
+            return match context.board.status() {
+                BoardStatus::Stalemate => 0,
+                _ => -(MATE - ply),
+            };
+        }
+        if depth == 0 {
+            return evaluate(&context.board);
+        }
+        for chess_move in moves {
+            history.push(chess_move);
+            let score = -self.negamax(history, depth - 1, -beta, -alpha, ply + 1);
+            history.pop();
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Board, Game, Square};
+    use std::str::FromStr;
+
+    fn history(fen: &str) -> History {
+        History::new(Board::from_str(fen).unwrap(), &Game::new())
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let history = history("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let engine = Engine::new(Strength::Casual, 0);
+        assert_eq!(
+            engine.best_move(&history, [0, 0]),
+            Some(ChessMove::new(Square::A1, Square::A8, None))
+        );
+    }
+
+    #[test]
+    fn plays_under_its_own_rule() {
+        // Stealo 5: the queen can't capture anything, so the free rook stays where it is.
+        let history = history("4k3/8/8/3r4/8/8/8/3QK3 w - - 0 1");
+        let engine = Engine::new(Strength::Expert, 0);
+        let chess_move = engine.best_move(&history, [5, 0]).unwrap();
+        assert_ne!(chess_move, ChessMove::new(Square::D1, Square::D5, None));
+        let chess_move = engine.best_move(&history, [0, 0]).unwrap();
+        assert_eq!(chess_move, ChessMove::new(Square::D1, Square::D5, None));
+    }
+
+    #[test]
+    fn lockout_is_a_loss() {
+        // Stealo 57: white has to begin with e2-e4 and then move the king to e2.
+        let engine = Engine::new(Strength::Casual, 0);
+        let blocked = history("rnbqkbnr/pppppppp/8/8/8/4P3/PPPP1PPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(engine.best_move(&blocked, [57, 0]), None);
+        // Taking e2 away from the king wins for black.
+        let start =
+            Board::from_str("rnbqkbnr/p1pppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let mut game = Game::new_with_board(start);
+        game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+        let history = History::new(start, &game);
+        assert_eq!(
+            engine.best_move(&history, [57, 0]),
+            Some(ChessMove::new(Square::C8, Square::A6, None))
+        );
+    }
+
+    #[test]
+    fn same_seed_same_move() {
+        let history = history("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let engine = Engine::new(Strength::Beginner, 7);
+        assert_eq!(
+            engine.best_move(&history, [0, 0]),
+            engine.best_move(&history, [0, 0])
+        );
+    }
+}
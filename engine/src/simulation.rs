@@ -0,0 +1,411 @@
+// Plays engines against each other to measure how much a rule costs. The player under the rule
+// meets the same opponent without one, switching colors every game. The same games are played
+// once more without the rule, and the rule costs the elo between the two scores, so a player
+// that is stronger or weaker than its opponent doesn't count as the rule.
+use crate::search::Engine;
+use crate::strength::Strength;
+use crate::uci::UciEngine;
+use chess::{Board, BoardStatus, ChessMove, Color, Game};
+use domain::filters::context::FilterContext;
+use domain::filters::history::History;
+use domain::move_generator::generate_moves;
+use domain::restrictiveness::{measure, Restrictiveness};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Clone, Debug)]
+pub enum Player {
+    Bot(Strength),
+    Uci(UciEngine),
+}
+
+impl Player {
+    // Like a player in a game, it knows its own rule but not the opponent's.
+    fn best_move(
+        &self,
+        history: &History,
+        rule: i32,
+        seed: u64,
+    ) -> std::io::Result<Option<ChessMove>> {
+        match self {
+            Player::Bot(strength) => {
+                let mut rules = [0; 2];
+                rules[history.board().side_to_move().to_index()] = rule;
+                Ok(Engine::new(*strength, seed).best_move(history, rules))
+            }
+            Player::Uci(engine) => engine.best_move(history, rule),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub player: Player,
+    pub opponent: Player,
+    // Per rule, half of them with white.
+    pub games: usize,
+    // Random allowed moves at the start, or engines without a margin play the same game every time.
+    pub opening_plies: usize,
+    // A game that gets this long is a draw, there is no repetition or fifty move rule here.
+    pub max_plies: usize,
+    pub threads: usize,
+    pub seed: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            player: Player::Bot(Strength::Beginner),
+            opponent: Player::Bot(Strength::Beginner),
+            games: 100,
+            opening_plies: 4,
+            max_plies: 200,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameOutcome {
+    pub winner: Option<Color>,
+    // The loser had legal moves, but its rule allowed none of them.
+    pub lockout: bool,
+    pub plies: usize,
+}
+
+// Plays from the history until the game ends, the players and rules in Color::to_index order.
+pub fn play_game(
+    mut history: History,
+    players: [&Player; 2],
+    rules: [i32; 2],
+    seed: u64,
+    settings: &Settings,
+) -> std::io::Result<GameOutcome> {
+    let mut plies = 0;
+    while plies < settings.max_plies {
+        let board = *history.board();
+        let side = board.side_to_move();
+        let rule = rules[side.to_index()];
+        let allowed = generate_moves(rule, &FilterContext::new(history.clone(), None));
+        if allowed.is_empty() {
+            return Ok(GameOutcome {
+                winner: (board.status() != BoardStatus::Stalemate).then_some(!side),
+                lockout: board.status() == BoardStatus::Ongoing,
+                plies,
+            });
+        }
+        let move_seed = mix(seed ^ plies as u64);
+        let chess_move = if plies < settings.opening_plies {
+            Some(allowed[(move_seed % allowed.len() as u64) as usize])
+        } else {
+            players[side.to_index()].best_move(&history, rule, move_seed)?
+        };
+        // Only a misbehaving engine gives up while it has moves, it loses like a lockout.
+        let Some(chess_move) = chess_move else {
+            return Ok(GameOutcome {
+                winner: Some(!side),
+                lockout: true,
+                plies,
+            });
+        };
+        history.push(chess_move);
+        plies += 1;
+    }
+    Ok(GameOutcome {
+        winner: None,
+        lockout: false,
+        plies,
+    })
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RuleReport {
+    pub rule_id: i32,
+    pub games: usize,
+    // Points per game for the player under the rule.
+    pub score: f64,
+    // Points per game for the player in the same games without a rule.
+    pub control_score: f64,
+    // How many games it lost by having no allowed moves, as a fraction of all games.
+    pub lockout_losses: f64,
+    pub average_plies: f64,
+    // The elo the rule takes away, what the rules table has as the rule's elo.
+    pub elo_penalty: f64,
+}
+
+impl RuleReport {
+    pub const CSV_HEADER: &'static str =
+        "rule_id,games,score,control_score,lockout_losses,average_plies,elo_penalty";
+
+    fn new(rule_id: i32, games: &[(Color, GameOutcome)], control: &[(Color, GameOutcome)]) -> Self {
+        let lockouts = games
+            .iter()
+            .filter(|(color, outcome)| outcome.lockout && outcome.winner != Some(*color))
+            .count();
+        let games_f = games.len().max(1) as f64;
+        let score = points_per_game(games);
+        let control_score = points_per_game(control);
+        Self {
+            rule_id,
+            games: games.len(),
+            score,
+            control_score,
+            lockout_losses: lockouts as f64 / games_f,
+            average_plies: games.iter().map(|(_, o)| o.plies).sum::<usize>() as f64 / games_f,
+            elo_penalty: elo_penalty(score, games.len())
+                - elo_penalty(control_score, control.len()),
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{:.4},{:.4},{:.4},{:.1},{:.0}",
+            self.rule_id,
+            self.games,
+            self.score,
+            self.control_score,
+            self.lockout_losses,
+            self.average_plies,
+            self.elo_penalty
+        )
+    }
+}
+
+// Points per game for the player with the given color.
+fn points_per_game(games: &[(Color, GameOutcome)]) -> f64 {
+    let points: f64 = games
+        .iter()
+        .map(|(color, outcome)| match outcome.winner {
+            Some(winner) if winner == *color => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        })
+        .sum();
+    points / games.len().max(1) as f64
+}
+
+// How much weaker than its opponent the score makes the player. A score of all or nothing would
+// be infinite, so it is taken as half a game away from that.
+pub fn elo_penalty(score: f64, games: usize) -> f64 {
+    let margin = 0.5 / games.max(1) as f64;
+    let score = score.clamp(margin, 1.0 - margin);
+    400.0 * ((1.0 - score) / score).log10()
+}
+
+// Every game of every rule is a job, taken by whichever thread is free. The control games without
+// a rule come last.
+pub fn simulate(rule_ids: &[i32], settings: &Settings) -> std::io::Result<Vec<RuleReport>> {
+    let series: Vec<i32> = rule_ids.iter().copied().chain([0]).collect();
+    let jobs: Vec<(usize, usize)> = (0..series.len())
+        .flat_map(|rule| (0..settings.games).map(move |game| (rule, game)))
+        .collect();
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(vec![Vec::new(); series.len()]);
+    let errors = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..settings.threads.max(1) {
+            scope.spawn(|| loop {
+                let Some(&(rule, game)) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                    return;
+                };
+                // The player under the rule takes white in the even games.
+                let color = if game % 2 == 0 {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                let mut players = [&settings.player; 2];
+                let mut rules = [0; 2];
+                players[(!color).to_index()] = &settings.opponent;
+                rules[color.to_index()] = series[rule];
+                let start = History::new(Board::default(), &Game::new());
+                // Both colors of a pair get the same opening, and every rule the same seeds as
+                // the control games.
+                let seed = mix(settings.seed ^ (game / 2) as u64);
+                match play_game(start, players, rules, seed, settings) {
+                    Ok(outcome) => outcomes.lock().unwrap()[rule].push((color, outcome)),
+                    // One broken engine would break every game, so the rest are skipped.
+                    Err(e) => {
+                        errors.lock().unwrap().push(e);
+                        next.store(jobs.len(), Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+    if let Some(error) = errors.into_inner().unwrap().pop() {
+        return Err(error);
+    }
+    let mut outcomes = outcomes.into_inner().unwrap();
+    let control = outcomes.pop().unwrap_or_default();
+    Ok(outcomes
+        .iter()
+        .zip(rule_ids)
+        .map(|(games, &rule_id)| RuleReport::new(rule_id, games, &control))
+        .collect())
+}
+
+// Measures the rules on every position of the games, each game is a job like in simulate. The
+// engine, if any, plays without a rule to find the best move of each position.
+pub fn measure_restrictiveness(
+    games: &[History],
+    rule_ids: &[i32],
+    engine: Option<&Player>,
+    threads: usize,
+) -> std::io::Result<Vec<Restrictiveness>> {
+    let next = AtomicUsize::new(0);
+    let totals = Mutex::new(
+        rule_ids
+            .iter()
+            .map(|&id| Restrictiveness::new(id))
+            .collect::<Vec<_>>(),
+    );
+    let errors = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let Some(game) = games.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                    return;
+                };
+                let best_move = |history: &History| {
+                    let player = engine?;
+                    player
+                        .best_move(history, 0, history.board().get_hash())
+                        .map_err(|e| errors.lock().unwrap().push(e))
+                        .ok()
+                        .flatten()
+                };
+                let results = measure(std::slice::from_ref(game), rule_ids, best_move);
+                if !errors.lock().unwrap().is_empty() {
+                    next.store(games.len(), Ordering::Relaxed);
+                    return;
+                }
+                for (total, result) in totals.lock().unwrap().iter_mut().zip(&results) {
+                    total.merge(result);
+                }
+            });
+        }
+    });
+    if let Some(error) = errors.into_inner().unwrap().pop() {
+        return Err(error);
+    }
+    Ok(totals.into_inner().unwrap())
+}
+
+// splitmix64, so nearby seeds still give unrelated moves.
+fn mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn quick() -> Settings {
+        Settings {
+            games: 4,
+            max_plies: 12,
+            threads: 2,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn even_score_costs_nothing() {
+        assert_eq!(elo_penalty(0.5, 100), 0.0);
+        assert!((elo_penalty(0.36, 100) - 100.0).abs() < 1.0);
+        assert!(elo_penalty(0.0, 10).is_finite());
+        assert!(elo_penalty(1.0, 10) < 0.0);
+    }
+
+    #[test]
+    fn lockout_loses_the_game() {
+        // Stealo 57: white has to begin with e2-e4, which is blocked here.
+        let board =
+            Board::from_str("rnbqkbnr/pppppppp/8/8/8/4P3/PPPP1PPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let history = History::new(board, &Game::new_with_board(board));
+        let player = Player::Bot(Strength::Beginner);
+        let outcome = play_game(history, [&player, &player], [57, 0], 0, &quick()).unwrap();
+        assert_eq!(
+            outcome,
+            GameOutcome {
+                winner: Some(Color::Black),
+                lockout: true,
+                plies: 0
+            }
+        );
+    }
+
+    #[test]
+    fn reports_every_rule() {
+        let reports = simulate(&[0, 57], &quick()).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|report| report.games == 4));
+        // Without a rule it's the same engine on both sides, capped games are draws.
+        assert!(reports[0].average_plies <= 12.0);
+        assert_eq!(reports[0].lockout_losses, 0.0);
+        assert_eq!(reports[1].to_csv().split(',').count(), 7);
+    }
+
+    #[test]
+    fn stronger_player_isnt_the_rules_cost() {
+        let settings = Settings {
+            player: Player::Bot(Strength::Casual),
+            ..quick()
+        };
+        // Without a rule it's the control games again.
+        let reports = simulate(&[0], &settings).unwrap();
+        assert_eq!(reports[0].score, reports[0].control_score);
+        assert_eq!(reports[0].elo_penalty, 0.0);
+        // Scoring 3.5 of 4 without the rule and half with it is what the rule costs, not 0.
+        let outcome = |winner| GameOutcome {
+            winner,
+            lockout: false,
+            plies: 40,
+        };
+        let control = [
+            (Color::White, outcome(Some(Color::White))),
+            (Color::Black, outcome(Some(Color::Black))),
+            (Color::White, outcome(Some(Color::White))),
+            (Color::Black, outcome(None)),
+        ];
+        let games = [
+            (Color::White, outcome(Some(Color::White))),
+            (Color::Black, outcome(Some(Color::White))),
+            (Color::White, outcome(None)),
+            (Color::Black, outcome(None)),
+        ];
+        let report = RuleReport::new(40, &games, &control);
+        assert_eq!(report.control_score, 0.875);
+        assert_eq!(report.score, 0.5);
+        assert!((report.elo_penalty - 338.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn restrictiveness_is_merged_over_games() {
+        let game = |moves: &[ChessMove]| {
+            let mut history = History::new(Board::default(), &Game::new());
+            moves
+                .iter()
+                .for_each(|&chess_move| history.push(chess_move));
+            history
+        };
+        let e4 = ChessMove::from_str("e2e4").unwrap();
+        let games = [game(&[e4]), game(&[])];
+        let player = Player::Bot(Strength::Beginner);
+        let results = measure_restrictiveness(&games, &[0, 40], Some(&player), 2).unwrap();
+        assert_eq!(results[0].positions, 3);
+        assert_eq!(results[0].best_move_forbidden(), Some(0.0));
+        // Stealo 40: can't play on the h file, in all three positions that's 3 of the 20 moves.
+        assert!((results[1].removed() - 0.15).abs() < 1e-9);
+        let results = measure_restrictiveness(&games, &[0], None, 1).unwrap();
+        assert_eq!(results[0].best_move_forbidden(), None);
+    }
+}
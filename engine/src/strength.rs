@@ -0,0 +1,66 @@
+// How well the engine plays, picked when starting a game against it.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Strength {
+    Beginner,
+    #[default]
+    Casual,
+    Club,
+    Expert,
+}
+
+impl Strength {
+    pub const ALL: [Strength; 4] = [
+        Strength::Beginner,
+        Strength::Casual,
+        Strength::Club,
+        Strength::Expert,
+    ];
+
+    // Levels start at 1, anything else is the nearest one.
+    pub fn from_level(level: u8) -> Self {
+        match level {
+            0 | 1 => Strength::Beginner,
+            2 => Strength::Casual,
+            3 => Strength::Club,
+            _ => Strength::Expert,
+        }
+    }
+
+    pub fn level(self) -> u8 {
+        match self {
+            Strength::Beginner => 1,
+            Strength::Casual => 2,
+            Strength::Club => 3,
+            Strength::Expert => 4,
+        }
+    }
+
+    // Half moves to look ahead.
+    pub fn depth(self) -> u8 {
+        self.level()
+    }
+
+    // How many centipawns a move may be worse than the best one and still be played,
+    // so the weaker levels make mistakes and don't always play the same game.
+    pub fn margin(self) -> i32 {
+        match self {
+            Strength::Beginner => 150,
+            Strength::Casual => 60,
+            Strength::Club => 20,
+            Strength::Expert => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_round_trip() {
+        for strength in Strength::ALL {
+            assert_eq!(Strength::from_level(strength.level()), strength);
+        }
+        assert_eq!(Strength::from_level(9), Strength::Expert);
+    }
+}
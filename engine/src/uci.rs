@@ -0,0 +1,216 @@
+use chess::ChessMove;
+use domain::filters::context::FilterContext;
+use domain::filters::history::History;
+use domain::move_generator::generate_moves;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long an engine gets to exit after quit, before it is killed.
+const QUIT_GRACE: Duration = Duration::from_millis(500);
+
+// An engine speaking UCI, like Stockfish, wearing an Elo Stealo rule. It doesn't know the rule,
+// so it only gets to search the moves the rule allows with go searchmoves. The process is
+// started for every move, a game against it keeps no state on the server.
+#[derive(Clone, Debug)]
+pub struct UciEngine {
+    pub path: PathBuf,
+    pub args: Vec<String>,
+    // How long the engine may think about a move.
+    pub movetime_ms: u64,
+    // How much longer than that it may take to answer, before it is killed.
+    pub margin_ms: u64,
+}
+
+impl UciEngine {
+    pub fn new(path: impl Into<PathBuf>, movetime_ms: u64) -> Self {
+        Self {
+            path: path.into(),
+            args: Vec::new(),
+            movetime_ms,
+            margin_ms: 2000,
+        }
+    }
+
+    // None when the rule leaves no moves, which loses the game. The engine isn't asked when
+    // there is nothing to choose. An engine that doesn't answer in time is killed, and the
+    // error is TimedOut. One that answers but doesn't exit after quit is killed too.
+    pub fn best_move(&self, history: &History, rule_id: i32) -> std::io::Result<Option<ChessMove>> {
+        let context = FilterContext::new(history.clone(), None);
+        let moves = generate_moves(rule_id, &context);
+        match moves.as_slice() {
+            [] => return Ok(None),
+            [only] => return Ok(Some(*only)),
+            _ => {}
+        }
+        let mut process = Command::new(&self.path)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut input = process.stdin.take().expect("stdin is piped");
+        let output = BufReader::new(process.stdout.take().expect("stdout is piped"));
+        // Reading blocks, so the lines come from a thread and can be waited for with a timeout.
+        // It ends when the engine's output is closed.
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in output.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let deadline =
+            Instant::now() + Duration::from_millis(self.movetime_ms.saturating_add(self.margin_ms));
+        let searchmoves: Vec<String> = moves.iter().map(|m| m.to_string()).collect();
+        let answer = (|| {
+            writeln!(input, "uci")?;
+            wait_for(&lines, "uciok", deadline)?;
+            writeln!(input, "isready")?;
+            wait_for(&lines, "readyok", deadline)?;
+            // Only the current position, bonus moves in the history aren't chess moves.
+            writeln!(input, "position fen {}", context.board)?;
+            writeln!(
+                input,
+                "go movetime {} searchmoves {}",
+                self.movetime_ms,
+                searchmoves.join(" ")
+            )?;
+            wait_for(&lines, "bestmove", deadline)
+        })();
+        match &answer {
+            Err(e) if e.kind() == ErrorKind::TimedOut => {
+                let _ = process.kill();
+            }
+            _ => {
+                let _ = writeln!(input, "quit");
+            }
+        }
+        drop(input);
+        let grace = Instant::now() + QUIT_GRACE;
+        while matches!(process.try_wait(), Ok(None)) && Instant::now() < grace {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = process.kill();
+        let _ = process.wait();
+        let answer = answer?;
+        let chess_move = answer
+            .split_whitespace()
+            .nth(1)
+            .and_then(|notation| ChessMove::from_str(notation).ok())
+            .filter(|chess_move| moves.contains(chess_move))
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, answer.clone()))?;
+        Ok(Some(chess_move))
+    }
+}
+
+// Skips everything else the engine prints, like its id and search info.
+fn wait_for(lines: &Receiver<String>, command: &str, deadline: Instant) -> std::io::Result<String> {
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let line = lines.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => Error::new(ErrorKind::TimedOut, command.to_string()),
+            RecvTimeoutError::Disconnected => {
+                Error::new(ErrorKind::UnexpectedEof, command.to_string())
+            }
+        })?;
+        if line.split_whitespace().next() == Some(command) {
+            return Ok(line.trim().to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Board, Game};
+
+    // Answers like an engine would, playing the last move it may search or the given one.
+    fn fake_engine(name: &str, bestmove: &str) -> UciEngine {
+        fake_engine_quitting(name, bestmove, "exit 0")
+    }
+
+    fn fake_engine_quitting(name: &str, bestmove: &str, on_quit: &str) -> UciEngine {
+        let script =
+            std::env::temp_dir().join(format!("fake_uci_{name}_{}.sh", std::process::id()));
+        std::fs::write(
+            &script,
+            format!(
+                r#"while read line; do
+  case "$line" in
+    uci) echo "id name Fake"; echo "uciok";;
+    isready) echo "readyok";;
+    go*) echo "info depth 1"; echo "bestmove {bestmove}";;
+    quit) {on_quit};;
+  esac
+done
+"#
+            ),
+        )
+        .unwrap();
+        UciEngine {
+            args: vec![script.to_string_lossy().to_string()],
+            ..UciEngine::new("sh", 10)
+        }
+    }
+
+    fn start() -> History {
+        History::new(Board::default(), &Game::new())
+    }
+
+    #[test]
+    fn searches_only_allowed_moves() {
+        // Stealo 40: can't play on the h file, so the last legal move h2h4 isn't offered.
+        let engine = fake_engine("last", "${line##* }");
+        let history = start();
+        let allowed = generate_moves(40, &FilterContext::new(start(), None));
+        assert_eq!(
+            engine.best_move(&history, 40).unwrap(),
+            allowed.last().copied()
+        );
+    }
+
+    #[test]
+    fn forbidden_answer_is_an_error() {
+        let engine = fake_engine("forbidden", "h2h4");
+        let error = engine.best_move(&start(), 40).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn silent_engine_times_out() {
+        let engine = UciEngine {
+            args: vec!["60".to_string()],
+            margin_ms: 100,
+            ..UciEngine::new("sleep", 10)
+        };
+        let started = Instant::now();
+        let error = engine.best_move(&start(), 0).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn engine_ignoring_quit_is_killed() {
+        let engine = fake_engine_quitting("stubborn", "e2e4", "exec sleep 60");
+        let started = Instant::now();
+        let e2e4 = ChessMove::from_str("e2e4").unwrap();
+        assert_eq!(engine.best_move(&start(), 0).unwrap(), Some(e2e4));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn lockout_doesnt_start_the_engine() {
+        // Stealo 57: white has to begin with e2-e4, which is blocked here.
+        let board =
+            Board::from_str("rnbqkbnr/pppppppp/8/8/8/4P3/PPPP1PPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let engine = UciEngine::new("/nonexistent/engine", 10);
+        let history = History::new(board, &Game::new_with_board(board));
+        assert_eq!(engine.best_move(&history, 57).unwrap(), None);
+    }
+}
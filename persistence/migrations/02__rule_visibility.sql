@@ -0,0 +1,4 @@
+ALTER TABLE games ADD COLUMN IF NOT EXISTS visibility SMALLINT NOT NULL DEFAULT 1;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS reveal_after SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS forbidden_attempt_white BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS forbidden_attempt_black BOOLEAN NOT NULL DEFAULT FALSE;
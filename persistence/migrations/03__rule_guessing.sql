@@ -0,0 +1,4 @@
+ALTER TABLE games ADD COLUMN IF NOT EXISTS max_guesses SMALLINT NOT NULL DEFAULT 3;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS guess_reward SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS guesses_white INTEGER[] NOT NULL DEFAULT '{}';
+ALTER TABLE games ADD COLUMN IF NOT EXISTS guesses_black INTEGER[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,2 @@
+ALTER TABLE games ADD COLUMN IF NOT EXISTS nonce VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE games ADD COLUMN IF NOT EXISTS commitment VARCHAR(64) NOT NULL DEFAULT '';
@@ -0,0 +1,5 @@
+INSERT INTO rules (id, name, elo, description) VALUES
+(60, 'Royal Manners', 500, 'Your queen may never give check.'),
+(61, 'Homebody', 750, 'Your king has to stay on your first two ranks.'),
+(62, 'Bishop''s Pair-ent', 750, 'While you have both bishops, you can''t leave either of them where it can be captured.'),
+(63, 'Careful Now', 1000, 'You can''t leave a piece en prise: attacked and undefended, or attacked by a cheaper piece.');
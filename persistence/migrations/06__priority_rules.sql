@@ -0,0 +1,4 @@
+INSERT INTO rules (id, name, elo, description) VALUES
+(64, 'Checkers', 1000, 'If you can capture something, you have to.'),
+(65, 'Pawn Stars', 1500, 'You always have to move your least valuable piece that can move.'),
+(66, 'Check It Out', 1000, 'If you can give check, you have to.');
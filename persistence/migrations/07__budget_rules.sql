@@ -0,0 +1,4 @@
+INSERT INTO rules (id, name, elo, description) VALUES
+(67, 'Pacifist-ish', 1000, 'You can only capture 5 times all game.'),
+(68, 'Limited Edition Queen', 750, 'Your queen can only make 8 moves all game.'),
+(69, 'Take Turns', 1000, 'You can''t move the same piece twice in a row.');
@@ -0,0 +1,4 @@
+INSERT INTO rules (id, name, elo, description) VALUES
+(70, 'Rusty Hinges', 750, 'Your a-rook may never move, so no long castling either.'),
+(71, 'Vegetarian Horse', 250, 'The knight that started on g1/g8 can''t capture.'),
+(72, 'Chosen One', 1500, 'The pawn that started on e2/e7 must promote. If it''s captured before that, you lose.');
@@ -0,0 +1,6 @@
+INSERT INTO rules (id, name, elo, description) VALUES
+(73, 'Homeless', 500, 'You may never castle.'),
+(74, 'The Long Way Round', 750, 'No short castling, and while you can still castle long your king and a-rook have to wait for it.'),
+(75, 'Lady Not Included', 250, 'You can only underpromote.'),
+(76, 'Glass Ceiling', 750, 'You can''t promote at all.'),
+(77, 'Spare Parts', 250, 'You can only promote to a piece you have lost.');
@@ -0,0 +1,5 @@
+INSERT INTO rules (id, name, elo, description) VALUES
+(78, 'Training Wheels', 250, 'Your queen can''t capture anything during the first 10 moves.'),
+(79, 'Gentle Giant', 500, 'While you are ahead in material, your queen can''t capture anything.'),
+(80, 'E-mbargo', 500, 'You can''t land any piece on the e-file until the first capture of the game.'),
+(81, 'Day And Night', 750, 'Your queen can only move to dark squares until move 15, and only to light squares after that.');
@@ -0,0 +1,6 @@
+ALTER TABLE games ADD COLUMN IF NOT EXISTS bonus_used_white BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS bonus_used_black BOOLEAN NOT NULL DEFAULT FALSE;
+INSERT INTO rules (id, name, elo, description) VALUES
+(82, 'Late Sprint', -250, 'Once per game, one of your pawns may move two squares forward from any rank.'),
+(83, 'Royal Steed', -250, 'Once per game, one of your knights may move like a king.'),
+(84, 'Take Backsies', -500, 'Once per game, you may take back your last move and your opponent''s reply.');
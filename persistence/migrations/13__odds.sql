@@ -0,0 +1,5 @@
+ALTER TABLE games ADD COLUMN IF NOT EXISTS material_odds SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS odds_giver SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS clock_white_ms BIGINT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS clock_black_ms BIGINT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS clock_running_since BIGINT;
@@ -0,0 +1,4 @@
+INSERT INTO rules (id, name, elo, description) VALUES
+(85, 'Heavy Artillery', 500, 'Lift both rooks to your third rank by your 10th move, in any order.'),
+(86, 'Knights Of The Rim', 250, 'Put both knights on the a- or h-file by your 6th move, in any order.'),
+(87, 'By The Book', 250, 'Castle and develop both knights and bishops by your 10th move, in any order.');
@@ -0,0 +1,3 @@
+ALTER TABLE games ADD COLUMN IF NOT EXISTS opponent SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS bot_color SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS bot_level SMALLINT NOT NULL DEFAULT 0;
@@ -1,6 +1,7 @@
 use crate::game_info::GameInfo;
 use crate::game_model::{chess_game_to_model, model_to_chess_game, GameModel};
 use crate::stealo_rule::StealoRule;
+use chess::Color;
 use domain::chessgame::ChessGame;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
@@ -26,8 +27,13 @@ impl EloStealoPostgresStore {
     pub async fn save_game(&self, id: Uuid, new_game: ChessGame) -> anyhow::Result<()> {
         let game_model = chess_game_to_model(&new_game);
         sqlx::query!(
-            r#"INSERT INTO games
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+            r#"INSERT INTO games (id, game, white, black, elo_white, elo_black, rule_id_white,
+            rule_id_black, visibility, reveal_after, forbidden_attempt_white, forbidden_attempt_black,
+            max_guesses, guess_reward, guesses_white, guesses_black, nonce, commitment, adaptive,
+            adaptive_rule, bonus_used_white, bonus_used_black, material_odds, odds_giver,
+            clock_white_ms, clock_black_ms, clock_running_since, opponent, bot_color, bot_level)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+            $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30)"#,
             id,
             game_model.game,
             game_model.white,
@@ -36,6 +42,28 @@ impl EloStealoPostgresStore {
             game_model.elo_black,
             game_model.rule_id_white,
             game_model.rule_id_black,
+            game_model.visibility,
+            game_model.reveal_after,
+            game_model.forbidden_attempt_white,
+            game_model.forbidden_attempt_black,
+            game_model.max_guesses,
+            game_model.guess_reward,
+            &game_model.guesses_white,
+            &game_model.guesses_black,
+            game_model.nonce,
+            game_model.commitment,
+            game_model.adaptive,
+            game_model.adaptive_rule,
+            game_model.bonus_used_white,
+            game_model.bonus_used_black,
+            game_model.material_odds,
+            game_model.odds_giver,
+            game_model.clock_white_ms,
+            game_model.clock_black_ms,
+            game_model.clock_running_since,
+            game_model.opponent,
+            game_model.bot_color,
+            game_model.bot_level,
         )
         .execute(&self.pool)
         .await?;
@@ -45,7 +73,11 @@ impl EloStealoPostgresStore {
     pub async fn get_game(&self, id: Uuid) -> anyhow::Result<ChessGame> {
         let game_model = sqlx::query_as!(
             GameModel,
-            r#"SELECT white, black, game, elo_white, elo_black, rule_id_white, rule_id_black
+            r#"SELECT white, black, game, elo_white, elo_black, rule_id_white, rule_id_black,
+            visibility, reveal_after, forbidden_attempt_white, forbidden_attempt_black,
+            max_guesses, guess_reward, guesses_white, guesses_black, nonce, commitment, adaptive,
+            adaptive_rule, bonus_used_white, bonus_used_black, material_odds, odds_giver,
+            clock_white_ms, clock_black_ms, clock_running_since, opponent, bot_color, bot_level
             FROM games WHERE id = $1"#,
             id
         )
@@ -55,13 +87,41 @@ impl EloStealoPostgresStore {
         Ok(chess_game)
     }
 
+    // The most recently started games, game ids are time ordered.
+    pub async fn get_recent_games(&self, limit: i64) -> anyhow::Result<Vec<ChessGame>> {
+        let game_models = sqlx::query_as!(
+            GameModel,
+            r#"SELECT white, black, game, elo_white, elo_black, rule_id_white, rule_id_black,
+            visibility, reveal_after, forbidden_attempt_white, forbidden_attempt_black,
+            max_guesses, guess_reward, guesses_white, guesses_black, nonce, commitment, adaptive,
+            adaptive_rule, bonus_used_white, bonus_used_black, material_odds, odds_giver,
+            clock_white_ms, clock_black_ms, clock_running_since, opponent, bot_color, bot_level
+            FROM games ORDER BY id DESC LIMIT $1"#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(game_models.into_iter().map(model_to_chess_game).collect())
+    }
+
     pub async fn update_game(&self, id: Uuid, game: &ChessGame) -> anyhow::Result<()> {
         let game_model = chess_game_to_model(game);
         sqlx::query!(
             r#"UPDATE games
-            SET game = $1
-            WHERE id = $2"#,
+            SET game = $1, forbidden_attempt_white = $2, forbidden_attempt_black = $3,
+            guesses_white = $4, guesses_black = $5, bonus_used_white = $6, bonus_used_black = $7,
+            clock_white_ms = $8, clock_black_ms = $9, clock_running_since = $10
+            WHERE id = $11"#,
             game_model.game,
+            game_model.forbidden_attempt_white,
+            game_model.forbidden_attempt_black,
+            &game_model.guesses_white,
+            &game_model.guesses_black,
+            game_model.bonus_used_white,
+            game_model.bonus_used_black,
+            game_model.clock_white_ms,
+            game_model.clock_black_ms,
+            game_model.clock_running_since,
             id
         )
         .execute(&self.pool)
@@ -72,16 +132,21 @@ impl EloStealoPostgresStore {
     pub async fn load_game_info(
         &self,
         id: Uuid,
-        color: String,
+        viewer: Option<Color>,
     ) -> anyhow::Result<GameInfo> {
         let game_model = sqlx::query_as!(
             GameModel,
-            r#"SELECT white, black, game, elo_white, elo_black, rule_id_white, rule_id_black FROM games
+            r#"SELECT white, black, game, elo_white, elo_black, rule_id_white, rule_id_black,
+            visibility, reveal_after, forbidden_attempt_white, forbidden_attempt_black,
+            max_guesses, guess_reward, guesses_white, guesses_black, nonce, commitment, adaptive,
+            adaptive_rule, bonus_used_white, bonus_used_black, material_odds, odds_giver,
+            clock_white_ms, clock_black_ms, clock_running_since, opponent, bot_color, bot_level
+            FROM games
             WHERE id = $1"#,
             id
         ).fetch_one(&self.pool).await?;
         let chess_game = model_to_chess_game(game_model);
-        Ok(GameInfo::new(chess_game, color))
+        Ok(GameInfo::new(chess_game, viewer))
     }
 
     pub async fn get_stealo_rules(&self) -> anyhow::Result<Vec<StealoRule>> {
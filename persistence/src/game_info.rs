@@ -1,46 +1,224 @@
+use chess::Color;
+use domain::chessgame::{ChessGame, Progress};
 use serde::{Deserialize, Serialize};
-use domain::chessgame::ChessGame;
+
+// Information one player isn't allowed to see yet. Serialized as {"revealed": value} or "hidden",
+// so a hidden rule can't be mistaken for rule 0 (normal chess).
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Secret<T> {
+    Revealed(T),
+    Hidden,
+}
+
+impl<T> Secret<T> {
+    pub fn reveal_if(visible: bool, value: T) -> Self {
+        if visible {
+            Secret::Revealed(value)
+        } else {
+            Secret::Hidden
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Secret<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Secret::Revealed(value),
+            None => Secret::Hidden,
+        }
+    }
+}
+
+// How far a player is with the goals of an obligation rule, due by the deadline move.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ObligationProgress {
+    pub done: u8,
+    pub total: u8,
+    pub deadline: u16,
+}
+
+impl From<Progress> for ObligationProgress {
+    fn from(progress: Progress) -> Self {
+        Self {
+            done: progress.done,
+            total: progress.total,
+            deadline: progress.deadline,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct GameInfo {
     pub white: String,
     pub black: String,
-    pub white_elo: i32,
-    pub black_elo: i32,
-    pub white_stealo: i32,
-    pub black_stealo: i32,
+    pub white_elo: Secret<i32>,
+    pub black_elo: Secret<i32>,
+    pub white_stealo: Secret<i32>,
+    pub black_stealo: Secret<i32>,
+    // Rule guessing side game: the guesses are only shown to the guesser until the game has ended.
+    pub white_guesses: Secret<Vec<i32>>,
+    pub black_guesses: Secret<Vec<i32>>,
+    pub white_guess_points: u32,
+    pub black_guess_points: u32,
+    pub guesses_left: Secret<u8>,
+    // Remaining budget of the rule, None if the rule doesn't have one.
+    pub white_budget: Secret<Option<u16>>,
+    pub black_budget: Secret<Option<u16>>,
+    // Progress of the rule's obligation, None if the rule doesn't have one.
+    pub white_progress: Secret<Option<ObligationProgress>>,
+    pub black_progress: Secret<Option<ObligationProgress>>,
+    // Bonus moves of the side to move, for its own player or once its rule is revealed.
+    pub bonus_moves: Secret<Vec<String>>,
+    // Commitment to both rules, the nonce to check it against is revealed when the game has ended.
+    pub commitment: Option<String>,
+    pub nonce: Secret<String>,
 }
 
 impl GameInfo {
-    pub fn new(chess_game: ChessGame, color: String) -> Self {
+    // The viewer is the seat the server bound to the requester, not a color the client claims.
+    // Everything else follows the game's visibility policy, see ChessGame::rule_revealed.
+    pub fn new(chess_game: ChessGame, viewer: Option<Color>) -> Self {
+        let white_visible = viewer == Some(Color::White) || chess_game.rule_revealed(Color::White);
+        let black_visible = viewer == Some(Color::Black) || chess_game.rule_revealed(Color::Black);
         let game_has_ended = chess_game.get_moves().is_empty();
-        let white_elo = if color == "white" || game_has_ended {
-            chess_game.elo_white
-        } else {
-            0
-        };
-        let white_stealo = if color == "white" || game_has_ended {
-            chess_game.rule_id_white
-        } else {
-            0
-        };
-        let black_elo = if color == "black" || game_has_ended {
-            chess_game.elo_black
-        } else {
-            0
-        };
-        let black_stealo = if color == "black" || game_has_ended {
-            chess_game.rule_id_black
-        } else {
-            0
+        let mover_visible = match chess_game.game.side_to_move() {
+            Color::White => white_visible,
+            Color::Black => black_visible,
         };
         Self {
+            bonus_moves: Secret::reveal_if(mover_visible, chess_game.get_bonus_moves_string()),
+            white_guesses: Secret::reveal_if(
+                viewer == Some(Color::White) || game_has_ended,
+                chess_game.guesses_white.clone(),
+            ),
+            black_guesses: Secret::reveal_if(
+                viewer == Some(Color::Black) || game_has_ended,
+                chess_game.guesses_black.clone(),
+            ),
+            white_guess_points: chess_game.guess_points(Color::White),
+            black_guess_points: chess_game.guess_points(Color::Black),
+            guesses_left: match viewer {
+                Some(color) => Secret::Revealed(chess_game.guesses_left(color)),
+                None => Secret::Hidden,
+            },
+            commitment: chess_game.commitment(),
+            nonce: chess_game.revealed_nonce().map(str::to_string).into(),
+            white_budget: Secret::reveal_if(
+                white_visible,
+                chess_game.remaining_budget(Color::White),
+            ),
+            black_budget: Secret::reveal_if(
+                black_visible,
+                chess_game.remaining_budget(Color::Black),
+            ),
+            white_progress: Secret::reveal_if(
+                white_visible,
+                chess_game.obligation_progress(Color::White).map(Into::into),
+            ),
+            black_progress: Secret::reveal_if(
+                black_visible,
+                chess_game.obligation_progress(Color::Black).map(Into::into),
+            ),
+            white_elo: Secret::reveal_if(white_visible, chess_game.elo_white),
+            black_elo: Secret::reveal_if(black_visible, chess_game.elo_black),
+            white_stealo: Secret::reveal_if(white_visible, chess_game.rule_id_white),
+            black_stealo: Secret::reveal_if(black_visible, chess_game.rule_id_black),
             white: chess_game.white,
             black: chess_game.black,
-            white_elo,
-            black_elo,
-            white_stealo,
-            black_stealo
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::chessgame::new_game;
+    use domain::visibility::RuleVisibility;
+
+    #[test]
+    fn opponent_rule_is_hidden() {
+        let game = new_game("White".to_string(), "Black".to_string(), 1800, 1200, 0, 37);
+        let info = GameInfo::new(game, Some(Color::White));
+        assert_eq!(info.white_stealo, Secret::Revealed(0));
+        assert_eq!(info.black_stealo, Secret::Hidden);
+        assert_eq!(info.black_elo, Secret::Hidden);
+        assert_eq!(info.white_budget, Secret::Revealed(None));
+        assert_eq!(info.black_budget, Secret::Hidden);
+    }
+
+    #[test]
+    fn owner_sees_obligation_progress() {
+        let game = new_game("White".to_string(), "Black".to_string(), 1800, 1200, 86, 86);
+        let info = GameInfo::new(game, Some(Color::White));
+        let progress = ObligationProgress {
+            done: 0,
+            total: 2,
+            deadline: 6,
+        };
+        assert_eq!(info.white_progress, Secret::Revealed(Some(progress)));
+        assert_eq!(info.black_progress, Secret::Hidden);
+    }
+
+    #[test]
+    fn bonus_moves_shown_to_their_owner() {
+        // Stealo 82: a pawn may step two squares from any rank, once.
+        let game = || {
+            let mut game = new_game("White".to_string(), "Black".to_string(), 1800, 1200, 82, 0);
+            game.make_move("d2d3".to_string(), None);
+            game.make_move("e7e5".to_string(), None);
+            game
+        };
+        let info = GameInfo::new(game(), Some(Color::Black));
+        assert_eq!(info.bonus_moves, Secret::Hidden);
+        let info = GameInfo::new(game(), Some(Color::White));
+        let Secret::Revealed(bonus_moves) = info.bonus_moves else {
+            panic!("the owner doesn't see its bonus moves");
+        };
+        assert!(bonus_moves.contains(&"+d3d5".to_string()));
+    }
+
+    #[test]
+    fn spectator_sees_no_rules() {
+        let game = new_game("White".to_string(), "Black".to_string(), 1800, 1200, 12, 37);
+        let info = GameInfo::new(game, None);
+        assert_eq!(info.white_stealo, Secret::Hidden);
+        assert_eq!(info.black_stealo, Secret::Hidden);
+    }
+
+    #[test]
+    fn rules_revealed_after_game_end() {
+        let mut game = new_game("White".to_string(), "Black".to_string(), 1800, 1200, 12, 37);
+        game.make_move("resign".to_string(), Some("black".to_string()));
+        let info = GameInfo::new(game, None);
+        assert_eq!(info.white_stealo, Secret::Revealed(12));
+        assert_eq!(info.black_stealo, Secret::Revealed(37));
+    }
+
+    #[test]
+    fn open_rules_are_visible_to_spectators() {
+        let mut game = new_game("White".to_string(), "Black".to_string(), 1800, 1200, 12, 37);
+        game.visibility = RuleVisibility::Open;
+        let info = GameInfo::new(game, None);
+        assert_eq!(info.white_stealo, Secret::Revealed(12));
+        assert_eq!(info.black_stealo, Secret::Revealed(37));
+    }
+
+    #[test]
+    fn guesses_shown_to_guesser_only() {
+        let mut game = new_game("White".to_string(), "Black".to_string(), 1800, 1200, 12, 37);
+        game.guess_rule(Color::White, 37).unwrap();
+        let info = GameInfo::new(game, Some(Color::Black));
+        assert_eq!(info.white_guesses, Secret::Hidden);
+        assert_eq!(info.black_guesses, Secret::Revealed(vec![]));
+        assert_eq!(info.white_guess_points, 3);
+    }
+
+    #[test]
+    fn hidden_serializes_distinct_from_rule_zero() {
+        let hidden = serde_json::to_string(&Secret::<i32>::Hidden).unwrap();
+        let none_rule = serde_json::to_string(&Secret::Revealed(0)).unwrap();
+        assert_eq!(hidden, "\"hidden\"");
+        assert_eq!(none_rule, "{\"revealed\":0}");
+    }
+}
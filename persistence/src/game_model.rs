@@ -1,6 +1,15 @@
 use anyhow::anyhow;
-use chess::{Action, Board, Color, Game, MoveGen};
+use chess::{Action, Board, Color, MoveGen, ALL_SQUARES};
+use domain::adaptive::Adaptive;
+use domain::bonus::{play, Bonus, BonusMove};
 use domain::chessgame::ChessGame;
+use domain::clock::Clock;
+use domain::gamestate::GameState;
+use domain::guessing::GuessReward;
+use domain::movecache::PositionCache;
+use domain::odds::MaterialOdds;
+use domain::opponent::Opponent;
+use domain::visibility::RuleVisibility;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -12,21 +21,87 @@ pub struct GameModel {
     pub elo_black: i32,
     pub rule_id_white: i32,
     pub rule_id_black: i32,
+    pub visibility: i16,
+    pub reveal_after: i16,
+    pub forbidden_attempt_white: bool,
+    pub forbidden_attempt_black: bool,
+    pub max_guesses: i16,
+    pub guess_reward: i16,
+    pub guesses_white: Vec<i32>,
+    pub guesses_black: Vec<i32>,
+    pub nonce: String,
+    pub commitment: String,
+    pub adaptive: i16,
+    pub adaptive_rule: i32,
+    pub bonus_used_white: bool,
+    pub bonus_used_black: bool,
+    pub material_odds: i16,
+    pub odds_giver: i16,
+    // All None for games without a clock.
+    pub clock_white_ms: Option<i64>,
+    pub clock_black_ms: Option<i64>,
+    pub clock_running_since: Option<i64>,
+    pub opponent: i16,
+    pub bot_color: i16,
+    pub bot_level: i16,
 }
 
 pub fn chess_game_to_model(chess_game: &ChessGame) -> GameModel {
+    let (visibility, reveal_after) = chess_game.visibility.to_parts();
+    let (adaptive, adaptive_rule) = chess_game.adaptive.to_parts();
+    let start = chess_game
+        .material_odds
+        .start_position(chess_game.odds_giver);
+    let clock = chess_game.clock;
+    let (opponent, bot_color, bot_level) = chess_game.opponent.to_parts();
     GameModel {
         white: chess_game.white.clone(),
         black: chess_game.black.clone(),
-        game: encode_game(&chess_game.game).unwrap(),
+        game: encode_game(start, &chess_game.earlier_games, &chess_game.game).unwrap(),
         elo_white: chess_game.elo_white,
         elo_black: chess_game.elo_black,
         rule_id_white: chess_game.rule_id_white,
         rule_id_black: chess_game.rule_id_black,
+        visibility,
+        reveal_after,
+        forbidden_attempt_white: chess_game.forbidden_attempt_white,
+        forbidden_attempt_black: chess_game.forbidden_attempt_black,
+        max_guesses: chess_game.max_guesses as i16,
+        guess_reward: chess_game.guess_reward.id(),
+        guesses_white: chess_game.guesses_white.clone(),
+        guesses_black: chess_game.guesses_black.clone(),
+        nonce: chess_game.nonce.clone(),
+        commitment: chess_game.commitment.clone(),
+        adaptive,
+        adaptive_rule,
+        bonus_used_white: chess_game.bonus_used_white,
+        bonus_used_black: chess_game.bonus_used_black,
+        material_odds: chess_game.material_odds.id(),
+        odds_giver: color_to_id(chess_game.odds_giver),
+        clock_white_ms: clock.map(|clock| clock.white_ms as i64),
+        clock_black_ms: clock.map(|clock| clock.black_ms as i64),
+        clock_running_since: clock.and_then(|clock| clock.running_since.map(|since| since as i64)),
+        opponent,
+        bot_color,
+        bot_level,
     }
 }
 
 pub fn model_to_chess_game(game_model: GameModel) -> ChessGame {
+    let material_odds = MaterialOdds::from_id(game_model.material_odds);
+    let odds_giver = color_from_id(game_model.odds_giver);
+    let start = material_odds.start_position(odds_giver);
+    let (earlier_games, game) = decode_game(game_model.game, start).unwrap();
+    let clock = match (game_model.clock_white_ms, game_model.clock_black_ms) {
+        (Some(white_ms), Some(black_ms)) => Some(Clock {
+            white_ms: white_ms.max(0) as u64,
+            black_ms: black_ms.max(0) as u64,
+            running_since: game_model
+                .clock_running_since
+                .map(|since| since.max(0) as u64),
+        }),
+        _ => None,
+    };
     ChessGame {
         white: game_model.white,
         black: game_model.black,
@@ -34,16 +109,75 @@ pub fn model_to_chess_game(game_model: GameModel) -> ChessGame {
         elo_black: game_model.elo_black,
         rule_id_white: game_model.rule_id_white,
         rule_id_black: game_model.rule_id_black,
-        game: decode_game(game_model.game).unwrap(),
+        game,
+        earlier_games,
+        visibility: RuleVisibility::from_parts(game_model.visibility, game_model.reveal_after),
+        forbidden_attempt_white: game_model.forbidden_attempt_white,
+        forbidden_attempt_black: game_model.forbidden_attempt_black,
+        max_guesses: game_model.max_guesses.clamp(0, u8::MAX as i16) as u8,
+        guess_reward: GuessReward::from_id(game_model.guess_reward),
+        guesses_white: game_model.guesses_white,
+        guesses_black: game_model.guesses_black,
+        nonce: game_model.nonce,
+        commitment: game_model.commitment,
+        adaptive: Adaptive::from_parts(game_model.adaptive, game_model.adaptive_rule),
+        bonus_used_white: game_model.bonus_used_white,
+        bonus_used_black: game_model.bonus_used_black,
+        material_odds,
+        odds_giver,
+        clock,
+        opponent: Opponent::from_parts(
+            game_model.opponent,
+            game_model.bot_color,
+            game_model.bot_level,
+        ),
+        moves_cache: PositionCache::default(),
+    }
+}
+
+fn color_to_id(color: Color) -> i16 {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+fn color_from_id(id: i16) -> Color {
+    match id {
+        1 => Color::Black,
+        _ => Color::White,
     }
 }
 
 // MoveGen is deterministic and the currently known position with the most allowed moves is 218.
 // Therefore, we can encode every move into a byte (number in MoveGen) and have space left for
 // special actions like offering draws and resigning which we'll put at the end of the byte range.
-fn encode_game(game: &Game) -> anyhow::Result<Vec<u8>> {
+// A bonus move is stored as 249 followed by the bonus and its source and destination square,
+// the moves after it are numbered in the position the bonus move left behind.
+// Games with material odds start from the odds position instead of the normal one.
+fn encode_game(
+    mut start: Board,
+    earlier_games: &[(GameState, BonusMove)],
+    game: &GameState,
+) -> anyhow::Result<Vec<u8>> {
     let mut result: Vec<u8> = Vec::with_capacity(game.actions().len());
-    let mut current_pos = Board::default();
+    for (earlier_game, bonus_move) in earlier_games {
+        let end = encode_actions(earlier_game, start, &mut result)?;
+        result.extend([
+            249,
+            bonus_move.bonus.id(),
+            bonus_move.source.to_int(),
+            bonus_move.dest.to_int(),
+        ]);
+        start = play(&end, bonus_move).ok_or_else(|| anyhow!("Cannot encode bonus move"))?;
+    }
+    encode_actions(game, start, &mut result)?;
+    Ok(result)
+}
+
+// Returns the position after the actions.
+fn encode_actions(game: &GameState, start: Board, result: &mut Vec<u8>) -> anyhow::Result<Board> {
+    let mut current_pos = start;
     for action in game.actions() {
         match action {
             Action::MakeMove(chess_move) => {
@@ -65,12 +199,16 @@ fn encode_game(game: &Game) -> anyhow::Result<Vec<u8>> {
             Action::DeclareDraw => result.push(250),
         }
     }
-    Ok(result)
+    Ok(current_pos)
 }
 
-fn decode_game(game: Vec<u8>) -> anyhow::Result<Game> {
-    let mut result = Game::new();
-    for action in game {
+type DecodedGame = (Vec<(GameState, BonusMove)>, GameState);
+
+fn decode_game(game: Vec<u8>, start: Board) -> anyhow::Result<DecodedGame> {
+    let mut earlier_games = Vec::new();
+    let mut result = GameState::new_with_board(start);
+    let mut bytes = game.into_iter();
+    while let Some(action) = bytes.next() {
         match action {
             255 => {
                 result.resign(Color::White);
@@ -90,6 +228,25 @@ fn decode_game(game: Vec<u8>) -> anyhow::Result<Game> {
             250 => {
                 result.declare_draw();
             }
+            249 => {
+                let mut next = || bytes.next().ok_or_else(|| anyhow!("Cannot decode game"));
+                let bonus = Bonus::from_id(next()?).ok_or_else(|| anyhow!("Unknown bonus"))?;
+                let source = *ALL_SQUARES
+                    .get(next()? as usize)
+                    .ok_or_else(|| anyhow!("Cannot decode game"))?;
+                let dest = *ALL_SQUARES
+                    .get(next()? as usize)
+                    .ok_or_else(|| anyhow!("Cannot decode game"))?;
+                let bonus_move = BonusMove {
+                    bonus,
+                    source,
+                    dest,
+                };
+                let board = play(&result.current_position(), &bonus_move)
+                    .ok_or_else(|| anyhow!("Cannot decode bonus move"))?;
+                let earlier = std::mem::replace(&mut result, GameState::new_with_board(board));
+                earlier_games.push((earlier, bonus_move));
+            }
             n => {
                 let mut moves = MoveGen::new_legal(&result.current_position());
                 let next_move = moves
@@ -99,7 +256,7 @@ fn decode_game(game: Vec<u8>) -> anyhow::Result<Game> {
             }
         }
     }
-    Ok(result)
+    Ok((earlier_games, result))
 }
 
 #[cfg(test)]
@@ -109,27 +266,27 @@ mod tests {
 
     #[test]
     pub fn test_encode_game() {
-        let mut game = Game::new();
+        let mut game = GameState::new();
         game.make_move(ChessMove::new(Square::E2, Square::E4, None));
         game.make_move(ChessMove::new(Square::E7, Square::E5, None));
-        let encoded = encode_game(&game).unwrap();
+        let encoded = encode_game(Board::default(), &[], &game).unwrap();
         assert_eq!(encoded, vec![9, 8]);
     }
 
     #[test]
     pub fn test_encode_game_special_actions() {
-        let mut game = Game::new();
+        let mut game = GameState::new();
         game.make_move(ChessMove::new(Square::E2, Square::E4, None));
         game.offer_draw(Color::White);
         game.resign(Color::White);
-        let encoded = encode_game(&game).unwrap();
+        let encoded = encode_game(Board::default(), &[], &game).unwrap();
         assert_eq!(encoded, vec![9, 253, 255]);
     }
 
     #[test]
     pub fn test_decode_game() {
         let db_game = vec![8, 9];
-        let game = decode_game(db_game).unwrap();
+        let (_, game) = decode_game(db_game, Board::default()).unwrap();
         assert_eq!(game.actions().len(), 2);
         assert!(game.result().is_none());
     }
@@ -137,8 +294,42 @@ mod tests {
     #[test]
     pub fn test_decode_game_special_actions() {
         let db_game = vec![8, 9, 252, 251];
-        let game = decode_game(db_game).unwrap();
+        let (_, game) = decode_game(db_game, Board::default()).unwrap();
         assert_eq!(game.actions().len(), 4);
         assert!(game.result().is_some());
     }
+
+    #[test]
+    pub fn test_bonus_move_round_trip() {
+        let mut game = GameState::new();
+        game.make_move(ChessMove::new(Square::E2, Square::E3, None));
+        game.make_move(ChessMove::new(Square::E7, Square::E6, None));
+        let bonus_move = BonusMove {
+            bonus: Bonus::LongPawnStep,
+            source: Square::E3,
+            dest: Square::E5,
+        };
+        let board = play(&game.current_position(), &bonus_move).unwrap();
+        let mut after = GameState::new_with_board(board);
+        after.make_move(ChessMove::new(Square::D7, Square::D6, None));
+        let encoded = encode_game(Board::default(), &[(game, bonus_move)], &after).unwrap();
+        assert_eq!(&encoded[2..6], &[249, 0, 20, 36]);
+        let (earlier_games, decoded) = decode_game(encoded, Board::default()).unwrap();
+        assert_eq!(earlier_games.len(), 1);
+        assert_eq!(earlier_games[0].1, bonus_move);
+        assert_eq!(decoded.current_position(), after.current_position());
+    }
+
+    #[test]
+    pub fn test_odds_game_round_trip() {
+        let mut chess_game =
+            domain::chessgame::new_game("White".to_string(), "Black".to_string(), 1200, 1800, 0, 0);
+        let time = domain::odds::TimeOdds::new(5, 3);
+        chess_game.give_odds(MaterialOdds::PawnAndMove, Some(time), Color::Black);
+        chess_game.make_move("e2e4".to_string(), None);
+        let decoded = model_to_chess_game(chess_game_to_model(&chess_game));
+        assert_eq!(decoded.get_position(), chess_game.get_position());
+        assert_eq!(decoded.material_odds, MaterialOdds::PawnAndMove);
+        assert_eq!(decoded.clock, chess_game.clock);
+    }
 }